@@ -0,0 +1,35 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use minesweeper::game_model::GameModel;
+
+const SIZE: usize = 1000;
+
+fn generation(c: &mut Criterion) {
+    let mut model = GameModel::with_seed(1);
+    c.bench_function("generate 1000x1000, 20% mines", |b| {
+        b.iter(|| model.start(SIZE, SIZE, SIZE * SIZE / 5).unwrap())
+    });
+    c.bench_function("generate 1000x1000, 99% mines", |b| {
+        b.iter(|| model.start(SIZE, SIZE, SIZE * SIZE / 100 * 99).unwrap())
+    });
+}
+
+fn flood_fill(c: &mut Criterion) {
+    c.bench_function("flood fill 1000x1000", |b| {
+        b.iter_batched(
+            || {
+                let mut model = GameModel::with_seed(1);
+                model.start(SIZE, SIZE, 0).unwrap();
+                model
+            },
+            |mut model| model.open_cell(SIZE / 2, SIZE / 2),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = generation, flood_fill
+}
+criterion_main!(benches);
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use crate::game_model::{GameEvent, GameModel};
+
+// Delay between rings of cells opened by the flood fill and the limit for the whole opening
+const RING_DELAY: Duration = Duration::from_millis(25);
+const MAX_REVEAL_DURATION: Duration = Duration::from_millis(600);
+const EXPLOSION_DURATION: Duration = Duration::from_millis(500);
+// Remaining mines appear one by one after the explosion
+const MINE_DELAY: Duration = Duration::from_millis(60);
+const MAX_MINES_DURATION: Duration = Duration::from_millis(1500);
+const CELEBRATION_DURATION: Duration = Duration::from_millis(2500);
+const CONFETTI_COUNT: usize = 80;
+const CONFETTI_COLORS: [Color; 5] = [
+    Color::RGB(240, 70, 70),
+    Color::RGB(250, 200, 40),
+    Color::RGB(70, 200, 90),
+    Color::RGB(60, 140, 240),
+    Color::RGB(200, 90, 220),
+];
+
+// Time based effects started by model events. The clock is advanced by the real frame
+// duration, so animations take the same time at any frame rate
+pub struct Animator {
+    is_enabled: bool,
+    time: Duration,
+    // time when the cell stops being drawn covered
+    reveals: HashMap<(usize, usize), Duration>,
+    // time when the mine is shown after the loss
+    mines: HashMap<(usize, usize), Duration>,
+    explosion: Option<((usize, usize), Duration)>,
+    celebration: Option<Duration>,
+    // time when all effects hiding the game result are over
+    end: Duration,
+}
+
+impl Animator {
+    pub fn new(is_enabled: bool) -> Self {
+        Self {
+            is_enabled,
+            time: Duration::ZERO,
+            reveals: HashMap::new(),
+            mines: HashMap::new(),
+            explosion: None,
+            celebration: None,
+            end: Duration::ZERO,
+        }
+    }
+
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.time += elapsed;
+        let time = self.time;
+        self.reveals.retain(|_, start| *start > time);
+        self.mines.retain(|_, start| *start > time);
+        if self
+            .explosion
+            .is_some_and(|(_, start)| time >= start + EXPLOSION_DURATION)
+        {
+            self.explosion = None;
+        }
+        if self
+            .celebration
+            .is_some_and(|start| time >= start + CELEBRATION_DURATION)
+        {
+            self.celebration = None;
+        }
+    }
+
+    // Ends all running effects
+    pub fn skip(&mut self) {
+        self.reveals.clear();
+        self.mines.clear();
+        self.explosion = None;
+        self.celebration = None;
+        self.end = self.time;
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent, model: &GameModel) {
+        if !self.is_enabled {
+            return;
+        }
+        match event {
+            GameEvent::CellsRevealed(cells) => self.start_reveal(cells),
+            GameEvent::Exploded(row, col) => self.start_explosion((*row, *col), model),
+            GameEvent::Won => {
+                let start = self.end.max(self.time);
+                self.celebration = Some(start);
+            }
+            GameEvent::Restarted => *self = Self::new(self.is_enabled),
+            GameEvent::Flagged(..) | GameEvent::Unflagged(..) => {}
+        }
+    }
+
+    fn start_reveal(&mut self, cells: &[(usize, usize)]) {
+        let Some(&(row, col)) = cells.first() else {
+            return;
+        };
+        let ring = |&(r, c): &(usize, usize)| r.abs_diff(row).max(c.abs_diff(col)) as u32;
+        let last_ring = cells.iter().map(ring).max().unwrap_or(0).max(1);
+        let delay = RING_DELAY.min(MAX_REVEAL_DURATION / last_ring);
+        for cell in &cells[1..] {
+            let start = self.time + delay * ring(cell);
+            self.reveals.insert(*cell, start);
+            self.end = self.end.max(start);
+        }
+    }
+
+    fn start_explosion(&mut self, exploded: (usize, usize), model: &GameModel) {
+        self.explosion = Some((exploded, self.time));
+        let (rows, cols) = model.board_size();
+        let distance =
+            |&(r, c): &(usize, usize)| r.abs_diff(exploded.0).max(c.abs_diff(exploded.1));
+        let mut mines = (0..rows * cols)
+            .map(|i| (i / cols, i % cols))
+            .filter(|&(r, c)| (r, c) != exploded && !model.get_cell(r, c).is_safe())
+            .collect::<Vec<_>>();
+        mines.sort_by_key(distance);
+        let delay = MINE_DELAY.min(MAX_MINES_DURATION / mines.len().max(1) as u32);
+        let first = self.time + EXPLOSION_DURATION / 2;
+        for (index, mine) in mines.into_iter().enumerate() {
+            let start = first + delay * index as u32;
+            self.mines.insert(mine, start);
+            self.end = self.end.max(start);
+        }
+        self.end = self.end.max(self.time + EXPLOSION_DURATION);
+    }
+
+    // Opened cell still waiting for its turn in the flood fill
+    pub fn is_covered(&self, row: usize, col: usize) -> bool {
+        self.reveals.contains_key(&(row, col))
+    }
+
+    // Mine not shown yet after the loss
+    pub fn is_mine_hidden(&self, row: usize, col: usize) -> bool {
+        self.mines.contains_key(&(row, col))
+    }
+
+    // Effects are over and the game result may be shown
+    pub fn is_finished(&self) -> bool {
+        self.time >= self.end
+    }
+
+    // Exploded cell and progress of the explosion from 0 to 1
+    pub fn explosion(&self) -> Option<((usize, usize), f64)> {
+        self.explosion
+            .map(|(cell, start)| (cell, self.progress(start, EXPLOSION_DURATION)))
+    }
+
+    // Falling confetti pieces in the window
+    pub fn confetti(&self, window: (u32, u32)) -> Vec<(Color, Rect)> {
+        let Some(start) = self.celebration else {
+            return Vec::new();
+        };
+        if self.time < start {
+            return Vec::new();
+        }
+        let progress = self.progress(start, CELEBRATION_DURATION);
+        let (width, height) = (window.0 as f64, window.1 as f64);
+        let size = (window.0.min(window.1) / 40).max(4);
+        (0..CONFETTI_COUNT)
+            .map(|index| {
+                // fixed pseudo random position, speed and sway of every piece
+                let random = |salt: usize| {
+                    let value = (index * 7919 + salt * 104_729) % 1000;
+                    value as f64 / 1000.0
+                };
+                let speed = 0.8 + random(1) * 0.7;
+                let sway = (progress * 4.0 * PI + random(2) * 2.0 * PI).sin() * size as f64 * 2.0;
+                let x = random(3) * width + sway;
+                let y = (progress * speed - random(4) * 0.4) * (height + size as f64) - size as f64;
+                let color = CONFETTI_COLORS[index % CONFETTI_COLORS.len()];
+                (color, Rect::new(x as i32, y as i32, size, size))
+            })
+            .collect()
+    }
+
+    fn progress(&self, start: Duration, duration: Duration) -> f64 {
+        let elapsed = self.time.saturating_sub(start);
+        (elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0)
+    }
+}
+
+// Fading flash around the exploded cell and the pulsing frame of its sprite
+pub fn explosion_frames(cell: Rect, progress: f64, color: Color) -> (Color, Rect, Rect) {
+    let scale = |frame: Rect, factor: f64| {
+        let width = (frame.width() as f64 * factor) as u32;
+        let height = (frame.height() as f64 * factor) as u32;
+        Rect::from_center(frame.center(), width.max(1), height.max(1))
+    };
+    let alpha = (color.a as f64 * (1.0 - progress)) as u8;
+    let flash = scale(cell, 1.0 + 2.0 * progress);
+    let sprite = scale(cell, 1.0 + 0.4 * (progress * PI).sin());
+    (Color::RGBA(color.r, color.g, color.b, alpha), flash, sprite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_model() -> GameModel {
+        let mut model = GameModel::with_seed(1);
+        model.start(10, 10, 10).unwrap();
+        model
+    }
+
+    #[test]
+    fn animation_reveal_spreads_by_rings() {
+        let model = create_model();
+        let mut animator = Animator::new(true);
+        let cells = vec![(5, 5), (5, 6), (4, 4), (5, 8), (0, 5)];
+        animator.handle_event(&GameEvent::CellsRevealed(cells), &model);
+        assert!(!animator.is_covered(5, 5));
+        assert!(animator.is_covered(5, 6));
+        assert!(!animator.is_finished());
+        animator.advance(RING_DELAY);
+        assert!(!animator.is_covered(5, 6) && !animator.is_covered(4, 4));
+        assert!(animator.is_covered(5, 8));
+        // time doesn't depend on how it's split into frames
+        for _ in 0..4 {
+            animator.advance(RING_DELAY);
+        }
+        assert!(!animator.is_covered(0, 5));
+        assert!(animator.is_finished());
+    }
+
+    #[test]
+    fn animation_loss_shows_mines_one_by_one() {
+        let model = create_model();
+        let mines = (0..100)
+            .map(|i| (i / 10, i % 10))
+            .filter(|&(r, c)| !model.get_cell(r, c).is_safe())
+            .collect::<Vec<_>>();
+        let mut animator = Animator::new(true);
+        animator.handle_event(&GameEvent::Exploded(mines[0].0, mines[0].1), &model);
+        assert_eq!(animator.explosion(), Some((mines[0], 0.0)));
+        assert!(mines[1..]
+            .iter()
+            .all(|&(r, c)| animator.is_mine_hidden(r, c)));
+
+        animator.advance(EXPLOSION_DURATION / 2);
+        let hidden = |animator: &Animator| {
+            mines
+                .iter()
+                .filter(|&&(r, c)| animator.is_mine_hidden(r, c))
+                .count()
+        };
+        assert_eq!(hidden(&animator), mines.len() - 2);
+        animator.advance(MINE_DELAY);
+        assert_eq!(hidden(&animator), mines.len() - 3);
+        animator.advance(MAX_MINES_DURATION);
+        assert_eq!(hidden(&animator), 0);
+        assert!(animator.explosion().is_none());
+        assert!(animator.is_finished());
+    }
+
+    #[test]
+    fn animation_celebration_and_restart() {
+        let model = create_model();
+        let mut animator = Animator::new(true);
+        animator.handle_event(&GameEvent::Won, &model);
+        animator.advance(CELEBRATION_DURATION / 2);
+        let confetti = animator.confetti((800, 600));
+        assert_eq!(confetti.len(), CONFETTI_COUNT);
+        assert!(confetti
+            .iter()
+            .any(|(_, rect)| (0..800).contains(&rect.x()) && (0..600).contains(&rect.y())));
+        animator.handle_event(&GameEvent::Restarted, &model);
+        assert!(animator.confetti((800, 600)).is_empty());
+    }
+
+    #[test]
+    fn animation_disabled() {
+        let model = create_model();
+        let mut animator = Animator::new(false);
+        animator.handle_event(&GameEvent::CellsRevealed(vec![(0, 0), (9, 9)]), &model);
+        animator.handle_event(&GameEvent::Exploded(0, 0), &model);
+        animator.handle_event(&GameEvent::Won, &model);
+        assert!(!animator.is_covered(9, 9));
+        assert!(animator.explosion().is_none());
+        assert!(animator.confetti((800, 600)).is_empty());
+        assert!(animator.is_finished());
+    }
+
+    #[test]
+    fn animation_explosion_frames() {
+        let cell = Rect::new(100, 100, 20, 20);
+        let (color, flash, sprite) = explosion_frames(cell, 0.0, Color::RGB(255, 0, 0));
+        assert_eq!((color.a, flash, sprite), (255, cell, cell));
+        let (color, flash, sprite) = explosion_frames(cell, 0.5, Color::RGB(255, 0, 0));
+        assert_eq!(color.a, 127);
+        assert_eq!(flash, Rect::new(90, 90, 40, 40));
+        assert_eq!(sprite, Rect::new(96, 96, 28, 28));
+    }
+}
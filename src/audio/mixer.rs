@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use sdl2::mixer::{self, Channel, Chunk, DEFAULT_FORMAT, MAX_VOLUME};
+use sdl2::{AudioSubsystem, Sdl};
+
+use super::{synthesize, AudioSink, Sound};
+use crate::resources::asset_path;
+
+const FREQUENCY: i32 = 44_100;
+const CHANNELS: i32 = 8;
+
+// Plays sounds through SDL mixer, files in assets/sounds/<name>.wav replace built-in sounds
+pub struct MixerAudio {
+    _subsystem: AudioSubsystem,
+    chunks: HashMap<Sound, Chunk>,
+}
+
+impl MixerAudio {
+    // Fails if there is no audio device, volume is in percents
+    pub fn open(sdl: &Sdl, volume: u8) -> Result<Self, String> {
+        let subsystem = sdl.audio()?;
+        mixer::open_audio(FREQUENCY, DEFAULT_FORMAT, 2, 1024)?;
+        // created right away to close the device on any following error
+        let mut audio = Self {
+            _subsystem: subsystem,
+            chunks: HashMap::new(),
+        };
+        let (frequency, format, channels) = mixer::query_spec()?;
+        if format != DEFAULT_FORMAT {
+            return Err(format!("Unsupported audio format {format:#x}"));
+        }
+        mixer::allocate_channels(CHANNELS);
+        Channel::all().set_volume(volume as i32 * MAX_VOLUME / 100);
+        for sound in Sound::ALL {
+            let chunk = Self::load(sound, frequency as u32, channels as usize)?;
+            audio.chunks.insert(sound, chunk);
+        }
+        Ok(audio)
+    }
+
+    fn load(sound: Sound, frequency: u32, channels: usize) -> Result<Chunk, String> {
+        let path = asset_path(&format!("sounds/{}.wav", sound.name()));
+        if path.is_file() {
+            return Chunk::from_file(path);
+        }
+        // every channel gets the same sample
+        let samples = synthesize(sound, frequency)
+            .into_iter()
+            .flat_map(|sample| std::iter::repeat_n(sample, channels))
+            .collect::<Vec<_>>();
+        Chunk::from_raw_buffer(samples.into_boxed_slice())
+    }
+}
+
+impl AudioSink for MixerAudio {
+    fn play(&mut self, sound: Sound) {
+        if let Some(chunk) = self.chunks.get(&sound) {
+            // all channels busy is not worth reporting
+            let _ = Channel::all().play(chunk, 0);
+        }
+    }
+}
+
+impl Drop for MixerAudio {
+    fn drop(&mut self) {
+        self.chunks.clear();
+        mixer::close_audio();
+    }
+}
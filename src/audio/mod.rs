@@ -0,0 +1,73 @@
+#[cfg(any(feature = "sound", test))]
+mod tones;
+#[cfg(feature = "sound")]
+pub use tones::*;
+
+#[cfg(feature = "sound")]
+mod mixer;
+#[cfg(feature = "sound")]
+pub use mixer::*;
+
+#[cfg(test)]
+mod recording;
+#[cfg(test)]
+pub use recording::*;
+
+use sdl2::Sdl;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Sound {
+    Reveal,
+    Cascade,
+    Flag,
+    Unflag,
+    Explosion,
+    Win,
+}
+
+// used only by the mixer output
+#[cfg_attr(not(feature = "sound"), allow(dead_code))]
+impl Sound {
+    pub const ALL: [Sound; 6] = [
+        Sound::Reveal,
+        Sound::Cascade,
+        Sound::Flag,
+        Sound::Unflag,
+        Sound::Explosion,
+        Sound::Win,
+    ];
+
+    // File name without extension used to override the built-in sound
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sound::Reveal => "reveal",
+            Sound::Cascade => "cascade",
+            Sound::Flag => "flag",
+            Sound::Unflag => "unflag",
+            Sound::Explosion => "explosion",
+            Sound::Win => "win",
+        }
+    }
+}
+
+pub trait AudioSink {
+    fn play(&mut self, sound: Sound);
+}
+
+// Silent output used when sound is not compiled in or no audio device exists
+pub struct NoAudio;
+
+impl AudioSink for NoAudio {
+    fn play(&mut self, _sound: Sound) {}
+}
+
+// Mixer output if the game is built with the `sound` feature and the device is available
+pub fn open_audio(sdl: &Sdl, volume: u8) -> Box<dyn AudioSink> {
+    #[cfg(feature = "sound")]
+    if let Ok(audio) = MixerAudio::open(sdl, volume) {
+        return Box::new(audio);
+    }
+    #[cfg(not(feature = "sound"))]
+    let _ = (sdl, volume);
+    Box::new(NoAudio)
+}
@@ -0,0 +1,16 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{AudioSink, Sound};
+
+// Remembers played sounds, the list is shared to be inspected after the sink is moved
+#[derive(Default)]
+pub struct RecordingAudio {
+    pub sounds: Rc<RefCell<Vec<Sound>>>,
+}
+
+impl AudioSink for RecordingAudio {
+    fn play(&mut self, sound: Sound) {
+        self.sounds.borrow_mut().push(sound);
+    }
+}
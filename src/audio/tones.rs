@@ -0,0 +1,71 @@
+use super::Sound;
+
+// Built-in sounds synthesized as mono 16-bit samples, used when no sound files are provided
+
+const AMPLITUDE: f64 = 0.3 * i16::MAX as f64;
+
+// Frequency in Hz (zero for noise) and duration in milliseconds of the notes played in sequence
+fn notes(sound: Sound) -> &'static [(f64, u32)] {
+    match sound {
+        Sound::Reveal => &[(880.0, 40)],
+        Sound::Cascade => &[(660.0, 30), (880.0, 30), (1100.0, 50)],
+        Sound::Flag => &[(1320.0, 50)],
+        Sound::Unflag => &[(990.0, 50)],
+        Sound::Explosion => &[(0.0, 450)],
+        Sound::Win => &[(523.0, 120), (659.0, 120), (784.0, 120), (1047.0, 320)],
+    }
+}
+
+pub fn synthesize(sound: Sound, sample_rate: u32) -> Vec<i16> {
+    let mut samples = Vec::new();
+    // deterministic pseudo random noise
+    let mut seed = 0x2545_f491_u32;
+    for &(frequency, duration) in notes(sound) {
+        let count = (sample_rate as u64 * duration as u64 / 1000) as usize;
+        for i in 0..count {
+            // linear fade out avoids clicks between notes
+            let envelope = 1.0 - i as f64 / count as f64;
+            let value = if frequency == 0.0 {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as f64 / u32::MAX as f64 * 2.0 - 1.0
+            } else {
+                let phase = (i as f64 * frequency / sample_rate as f64).fract();
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            };
+            samples.push((value * envelope * AMPLITUDE) as i16);
+        }
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tones_synthesize() {
+        for sound in Sound::ALL {
+            let samples = synthesize(sound, 44_100);
+            let duration = notes(sound).iter().map(|(_, ms)| ms).sum::<u32>();
+            assert_eq!(
+                samples.len(),
+                44_100 * duration as usize / 1000,
+                "{sound:?}"
+            );
+            assert!(samples.iter().any(|&sample| sample != 0));
+            assert!(samples
+                .iter()
+                .all(|&sample| (sample as f64).abs() <= AMPLITUDE));
+        }
+        assert_eq!(
+            synthesize(Sound::Explosion, 8000),
+            synthesize(Sound::Explosion, 8000)
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use sdl2::keyboard::Keycode;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum KeyAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Open,
+    Flag,
+    Chord,
+    // moves the cursor to the next covered cell
+    NextCovered,
+    // layers of the 3D mode
+    LayerUp,
+    LayerDown,
+    // puzzles of the pack
+    NextPuzzle,
+    PreviousPuzzle,
+    Restart,
+    Review,
+    SaveReplay,
+    Screenshot,
+    Theme,
+    Mute,
+    Quit,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 19] = [
+        KeyAction::Up,
+        KeyAction::Down,
+        KeyAction::Left,
+        KeyAction::Right,
+        KeyAction::Open,
+        KeyAction::Flag,
+        KeyAction::Chord,
+        KeyAction::NextCovered,
+        KeyAction::LayerUp,
+        KeyAction::LayerDown,
+        KeyAction::NextPuzzle,
+        KeyAction::PreviousPuzzle,
+        KeyAction::Restart,
+        KeyAction::Review,
+        KeyAction::SaveReplay,
+        KeyAction::Screenshot,
+        KeyAction::Theme,
+        KeyAction::Mute,
+        KeyAction::Quit,
+    ];
+
+    // Name used in the configuration file
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyAction::Up => "up",
+            KeyAction::Down => "down",
+            KeyAction::Left => "left",
+            KeyAction::Right => "right",
+            KeyAction::Open => "open",
+            KeyAction::Flag => "flag",
+            KeyAction::Chord => "chord",
+            KeyAction::NextCovered => "next",
+            KeyAction::LayerUp => "layer_up",
+            KeyAction::LayerDown => "layer_down",
+            KeyAction::NextPuzzle => "next_puzzle",
+            KeyAction::PreviousPuzzle => "previous_puzzle",
+            KeyAction::Restart => "restart",
+            KeyAction::Review => "review",
+            KeyAction::SaveReplay => "save_replay",
+            KeyAction::Screenshot => "screenshot",
+            KeyAction::Theme => "theme",
+            KeyAction::Mute => "mute",
+            KeyAction::Quit => "quit",
+        }
+    }
+
+    fn default_keys(&self) -> Vec<Keycode> {
+        match self {
+            KeyAction::Up => vec![Keycode::Up, Keycode::W, Keycode::K],
+            KeyAction::Down => vec![Keycode::Down, Keycode::S, Keycode::J],
+            KeyAction::Left => vec![Keycode::Left, Keycode::A, Keycode::H],
+            KeyAction::Right => vec![Keycode::Right, Keycode::D, Keycode::L],
+            KeyAction::Open => vec![Keycode::Return, Keycode::O],
+            KeyAction::Flag => vec![Keycode::F],
+            KeyAction::Chord => vec![Keycode::C],
+            KeyAction::NextCovered => vec![Keycode::N, Keycode::Tab],
+            KeyAction::LayerUp => vec![Keycode::PageUp, Keycode::Q],
+            KeyAction::LayerDown => vec![Keycode::PageDown, Keycode::E],
+            KeyAction::NextPuzzle => vec![Keycode::RightBracket, Keycode::Period],
+            KeyAction::PreviousPuzzle => vec![Keycode::LeftBracket, Keycode::Comma],
+            KeyAction::Restart => vec![Keycode::Space],
+            KeyAction::Review => vec![Keycode::R],
+            KeyAction::SaveReplay => vec![Keycode::F5],
+            KeyAction::Screenshot => vec![Keycode::F12],
+            KeyAction::Theme => vec![Keycode::T],
+            KeyAction::Mute => vec![Keycode::M],
+            KeyAction::Quit => vec![Keycode::Escape],
+        }
+    }
+}
+
+// Keyboard layout of the game, every key triggers at most one action
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    actions: HashMap<Keycode, KeyAction>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let actions = KeyAction::ALL
+            .iter()
+            .flat_map(|action| action.default_keys().into_iter().map(|key| (key, *action)))
+            .collect();
+        Self { actions }
+    }
+}
+
+impl KeyBindings {
+    pub fn action(&self, keycode: Keycode) -> Option<KeyAction> {
+        self.actions.get(&keycode).copied()
+    }
+
+    // Replaces keys of the action, the keys are taken from other actions
+    pub fn bind(&mut self, action: KeyAction, keys: &[Keycode]) {
+        self.actions.retain(|_, bound| *bound != action);
+        for key in keys {
+            self.actions.insert(*key, action);
+        }
+    }
+
+    // Applies `key.<action> = <key>, <key>...` configuration entry
+    pub fn parse_entry(&mut self, action: &str, value: &str) -> Result<(), String> {
+        let action = KeyAction::ALL
+            .into_iter()
+            .find(|known| known.name() == action)
+            .ok_or_else(|| format!("Unknown key action '{action}'"))?;
+        let keys = value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| parse_key(name).ok_or_else(|| format!("Unknown key '{name}'")))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.bind(action, &keys);
+        Ok(())
+    }
+}
+
+// Letters, digits, brackets, comma, period, F1-F12 and common named keys, case insensitive
+pub fn parse_key(name: &str) -> Option<Keycode> {
+    let lower = name.to_lowercase();
+    if let [char] = lower.as_bytes() {
+        // keycodes of these characters are their ASCII codes
+        return match char {
+            b'a'..=b'z' | b'0'..=b'9' | b'[' | b']' | b',' | b'.' => {
+                Keycode::from_i32(*char as i32)
+            }
+            _ => None,
+        };
+    }
+    let key = match lower.as_str() {
+        "up" => Keycode::Up,
+        "down" => Keycode::Down,
+        "left" => Keycode::Left,
+        "right" => Keycode::Right,
+        "return" | "enter" => Keycode::Return,
+        "space" => Keycode::Space,
+        "tab" => Keycode::Tab,
+        "escape" | "esc" => Keycode::Escape,
+        "backspace" => Keycode::Backspace,
+        "delete" => Keycode::Delete,
+        "insert" => Keycode::Insert,
+        "home" => Keycode::Home,
+        "end" => Keycode::End,
+        "pageup" => Keycode::PageUp,
+        "pagedown" => Keycode::PageDown,
+        "f1" => Keycode::F1,
+        "f2" => Keycode::F2,
+        "f3" => Keycode::F3,
+        "f4" => Keycode::F4,
+        "f5" => Keycode::F5,
+        "f6" => Keycode::F6,
+        "f7" => Keycode::F7,
+        "f8" => Keycode::F8,
+        "f9" => Keycode::F9,
+        "f10" => Keycode::F10,
+        "f11" => Keycode::F11,
+        "f12" => Keycode::F12,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_defaults() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.action(Keycode::K), Some(KeyAction::Up));
+        assert_eq!(bindings.action(Keycode::D), Some(KeyAction::Right));
+        assert_eq!(bindings.action(Keycode::Return), Some(KeyAction::Open));
+        assert_eq!(bindings.action(Keycode::Z), None);
+    }
+
+    #[test]
+    fn bindings_parse_entry() {
+        let mut bindings = KeyBindings::default();
+        bindings.parse_entry("flag", "Space, x").unwrap();
+        assert_eq!(bindings.action(Keycode::Space), Some(KeyAction::Flag));
+        assert_eq!(bindings.action(Keycode::X), Some(KeyAction::Flag));
+        assert_eq!(bindings.action(Keycode::F), None);
+
+        assert!(bindings.parse_entry("jump", "J").is_err());
+        assert!(bindings.parse_entry("open", "Ctrl").is_err());
+        assert_eq!(parse_key("f10"), Some(Keycode::F10));
+        assert_eq!(parse_key("7"), Some(Keycode::Num7));
+        assert_eq!(parse_key("]"), Some(Keycode::RightBracket));
+        assert_eq!(parse_key("+"), None);
+    }
+}
@@ -0,0 +1,70 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::{hidden_cells, Action, Player};
+use crate::game_model::{CellView, PlayerView};
+
+// Applies the single cell rule: if a number is satisfied by flags its other
+// neighbours are safe, if it equals the covered neighbours count they are mines
+// (full of mines when cells may hold several).
+// Falls back to a random click
+pub struct SingleRuleDeducer {
+    rng: StdRng,
+}
+
+impl SingleRuleDeducer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn deduce(&self, view: &PlayerView) -> Option<Action> {
+        let (rows, cols) = view.board_size();
+        for row in 0..rows {
+            for col in 0..cols {
+                let CellView::Revealed(mines) = view.cell(row, col) else {
+                    continue;
+                };
+                let flagged = view
+                    .neighbours(row, col)
+                    .map(|(r, c)| match view.cell(r, c) {
+                        CellView::Flagged(flags) => flags,
+                        _ => 0,
+                    })
+                    .sum::<usize>();
+                let hidden = view
+                    .neighbours(row, col)
+                    .filter(|(r, c)| view.cell(*r, *c) == CellView::Hidden)
+                    .collect::<Vec<_>>();
+                let Some(&(r, c)) = hidden.first() else {
+                    continue;
+                };
+                if mines == flagged {
+                    return Some(Action::Open(r, c));
+                }
+                if mines == flagged + hidden.len() * view.max_cell_mines() {
+                    return Some(Action::Flag(r, c));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Player for SingleRuleDeducer {
+    fn name(&self) -> &str {
+        "deducer"
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> Action {
+        if let Some(action) = self.deduce(view) {
+            return action;
+        }
+        let (row, col) = *hidden_cells(view)
+            .choose(&mut self.rng)
+            .expect("No hidden cells left");
+        Action::Open(row, col)
+    }
+}
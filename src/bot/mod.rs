@@ -0,0 +1,30 @@
+use crate::game_model::{CellView, Move, PlayerView};
+
+mod random_clicker;
+pub use random_clicker::*;
+
+mod deducer;
+pub use deducer::*;
+
+mod solver_player;
+pub use solver_player::*;
+
+mod tournament;
+pub use tournament::*;
+
+// Bots play with the same moves as a human
+pub type Action = Move;
+
+// Strategy that plays the game seeing only what a human player would see
+pub trait Player {
+    fn name(&self) -> &str;
+    fn next_action(&mut self, view: &PlayerView) -> Action;
+}
+
+fn hidden_cells(view: &PlayerView) -> Vec<(usize, usize)> {
+    let (rows, cols) = view.board_size();
+    (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (row, col)))
+        .filter(|(row, col)| view.cell(*row, *col) == CellView::Hidden)
+        .collect()
+}
@@ -0,0 +1,32 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::{hidden_cells, Action, Player};
+use crate::game_model::PlayerView;
+
+// Opens random covered cells, baseline for other strategies
+pub struct RandomClicker {
+    rng: StdRng,
+}
+
+impl RandomClicker {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for RandomClicker {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> Action {
+        let (row, col) = *hidden_cells(view)
+            .choose(&mut self.rng)
+            .expect("No hidden cells left");
+        Action::Open(row, col)
+    }
+}
@@ -0,0 +1,46 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::{Action, Player};
+use crate::game_model::{CellView, PlayerView};
+use crate::solver;
+
+// Opens cells proven safe by the constraint solver,
+// otherwise guesses the cell with the lowest mine probability
+pub struct SolverPlayer {
+    rng: StdRng,
+}
+
+impl SolverPlayer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Player for SolverPlayer {
+    fn name(&self) -> &str {
+        "solver"
+    }
+
+    fn next_action(&mut self, view: &PlayerView) -> Action {
+        let analysis = solver::analyze(view);
+        let is_hidden = |(row, col): &(usize, usize)| view.cell(*row, *col) == CellView::Hidden;
+        let safe = analysis
+            .safe_cells()
+            .into_iter()
+            .filter(is_hidden)
+            .collect::<Vec<_>>();
+        let candidates = if safe.is_empty() {
+            analysis.safest_cells()
+        } else {
+            safe
+        };
+        let (row, col) = *candidates
+            .choose(&mut self.rng)
+            .expect("No hidden cells left");
+        Action::Open(row, col)
+    }
+}
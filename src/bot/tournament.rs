@@ -0,0 +1,164 @@
+use super::{Player, RandomClicker, SingleRuleDeducer, SolverPlayer};
+use crate::config::Difficulty;
+use crate::game_model::{GameModel, State};
+
+pub struct GameResult {
+    pub is_win: bool,
+    pub steps: usize,
+    pub three_bv: usize,
+}
+
+#[derive(Default)]
+pub struct Summary {
+    pub name: String,
+    pub games: usize,
+    pub wins: usize,
+    pub steps: usize,
+    won_three_bv: usize,
+    won_steps: usize,
+}
+
+impl Summary {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+
+    pub fn average_steps(&self) -> f64 {
+        self.steps as f64 / self.games.max(1) as f64
+    }
+
+    // 3BV per action over won games
+    pub fn efficiency(&self) -> Option<f64> {
+        if self.won_steps == 0 {
+            return None;
+        }
+        Some(self.won_three_bv as f64 / self.won_steps as f64)
+    }
+
+    fn add(&mut self, result: &GameResult) {
+        self.games += 1;
+        self.steps += result.steps;
+        if result.is_win {
+            self.wins += 1;
+            self.won_three_bv += result.three_bv;
+            self.won_steps += result.steps;
+        }
+    }
+}
+
+pub fn play_game(player: &mut dyn Player, model: &mut GameModel) -> GameResult {
+    let (rows, cols) = model.board_size();
+    // protection from strategies repeating useless actions
+    let steps_limit = 4 * rows * cols;
+    let mut steps = 0;
+    while model.state() == State::InProgress && steps < steps_limit {
+        let action = player.next_action(&model.player_view());
+        model.apply(action);
+        steps += 1;
+    }
+    GameResult {
+        is_win: model.state() == State::Win,
+        steps,
+        three_bv: model.three_bv(),
+    }
+}
+
+// Every player plays the same sequence of seeded boards
+pub fn run_tournament(
+    players: &mut [Box<dyn Player>],
+    difficulty: Difficulty,
+    games: usize,
+    seed: u64,
+) -> Vec<Summary> {
+    players
+        .iter_mut()
+        .map(|player| {
+            let mut summary = Summary {
+                name: player.name().to_string(),
+                ..Default::default()
+            };
+            for game in 0..games {
+                let mut model = GameModel::with_seed(seed.wrapping_add(game as u64));
+                model
+                    .start(
+                        difficulty.row_count(),
+                        difficulty.col_count(),
+                        difficulty.mines_count(),
+                    )
+                    .expect("Difficulty presets fit their mines");
+                summary.add(&play_game(player.as_mut(), &mut model));
+            }
+            summary
+        })
+        .collect()
+}
+
+// Usage: bench [difficulty] [games] [seed]
+pub fn run_tournament_cli(args: &[String]) -> Result<(), String> {
+    let difficulty = match args.first() {
+        Some(value) => value.parse::<Difficulty>()?,
+        None => Difficulty::Beginner,
+    };
+    let games = match args.get(1) {
+        Some(value) => value.parse::<usize>().map_err(|e| e.to_string())?,
+        None => 100,
+    };
+    let seed = match args.get(2) {
+        Some(value) => value.parse::<u64>().map_err(|e| e.to_string())?,
+        None => 0,
+    };
+    let mut players: Vec<Box<dyn Player>> = vec![
+        Box::new(RandomClicker::new(seed)),
+        Box::new(SingleRuleDeducer::new(seed)),
+        Box::new(SolverPlayer::new(seed)),
+    ];
+    println!("Difficulty: {difficulty:?}, games: {games}, seed: {seed}");
+    println!(
+        "{:<10} {:>9} {:>10} {:>10}",
+        "player", "win rate", "avg steps", "3BV/step"
+    );
+    for summary in run_tournament(&mut players, difficulty, games, seed) {
+        let efficiency = summary
+            .efficiency()
+            .map_or("-".to_string(), |value| format!("{value:.3}"));
+        println!(
+            "{:<10} {:>8.1}% {:>10.1} {:>10}",
+            summary.name,
+            summary.win_rate() * 100.0,
+            summary.average_steps(),
+            efficiency
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tournament_is_deterministic() {
+        let run = || {
+            let mut players: Vec<Box<dyn Player>> = vec![
+                Box::new(SingleRuleDeducer::new(7)),
+                Box::new(SolverPlayer::new(7)),
+            ];
+            run_tournament(&mut players, Difficulty::Beginner, 10, 7)
+                .iter()
+                .map(|s| (s.wins, s.steps))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn tournament_solver_beats_random() {
+        let mut players: Vec<Box<dyn Player>> = vec![
+            Box::new(RandomClicker::new(3)),
+            Box::new(SolverPlayer::new(3)),
+        ];
+        let summaries = run_tournament(&mut players, Difficulty::Beginner, 30, 3);
+        assert!(summaries[1].wins > summaries[0].wins);
+        assert!(summaries[1].efficiency().unwrap() <= 1.0);
+    }
+}
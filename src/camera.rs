@@ -0,0 +1,535 @@
+use std::ops::Range;
+
+use sdl2::rect::{Point, Rect};
+
+const ZOOM_FACTOR: f64 = 1.25;
+const MINIMAP_MARGIN: i32 = 8;
+
+// Whether the point relative to the frame corner is inside the pointy-top hexagon inscribed
+// in the frame, its slanted edges take a quarter of the height at the top and at the bottom
+pub fn is_in_hexagon((width, height): (u32, u32), x: i32, y: i32) -> bool {
+    let (width, height) = (width as f64, height as f64);
+    // pixel centers
+    let (x, y) = (x as f64 + 0.5, y as f64 + 0.5);
+    if !(0.0..width).contains(&x) || !(0.0..height).contains(&y) {
+        return false;
+    }
+    let edge = y.min(height - y);
+    (x - width / 2.0).abs() <= width / 2.0 * (edge * 4.0 / height).min(1.0)
+}
+
+// Maps board cells to window pixels, supports zooming and panning of boards
+// not fitting into the window
+pub struct Camera {
+    min_cell_size: u32,
+    max_cell_size: u32,
+    cell_size: u32,
+    origin: Point,
+    viewport: (u32, u32),
+    board: (usize, usize),
+    // columns reserved at both sides of the board for wrapped copies
+    margin: usize,
+    // odd rows are shifted right by half a cell
+    is_hex: bool,
+}
+
+impl Camera {
+    pub fn new(min_cell_size: u32, max_cell_size: u32) -> Self {
+        Self {
+            min_cell_size,
+            max_cell_size: max_cell_size.max(min_cell_size),
+            cell_size: min_cell_size,
+            origin: Point::new(0, 0),
+            viewport: (0, 0),
+            board: (0, 0),
+            margin: 0,
+            is_hex: false,
+        }
+    }
+
+    // Hex cells are laid out in rows shifted by half a cell, applies from the next fit
+    pub fn set_hex(&mut self, is_hex: bool) {
+        self.is_hex = is_hex;
+    }
+
+    // Applies from the next fit
+    pub fn set_margin(&mut self, cols: usize) {
+        self.margin = cols;
+    }
+
+    // Shows the whole board if possible keeping cells not smaller than minimal size
+    pub fn fit(&mut self, viewport: (u32, u32), board: (usize, usize)) {
+        self.viewport = viewport;
+        self.board = board;
+        let rows = board.0.max(1) as u32;
+        // in halves of a cell
+        let width = 2 * (board.1 + 2 * self.margin).max(1) as u32 + self.is_shifted() as u32;
+        self.cell_size = (viewport.0 * 2 / width)
+            .min(viewport.1 / rows)
+            .clamp(self.min_cell_size, self.max_cell_size);
+        self.origin = Point::new(0, 0);
+        self.clamp();
+    }
+
+    #[cfg(test)]
+    pub fn cell_size(&self) -> u32 {
+        self.cell_size
+    }
+
+    pub fn is_board_visible(&self) -> bool {
+        let (width, height) = self.content_pixel_size();
+        width <= self.viewport.0 as i64 && height <= self.viewport.1 as i64
+    }
+
+    // Positive steps zoom in, the point under the anchor stays in place
+    pub fn zoom(&mut self, steps: i32, anchor: Point) {
+        let scale = ZOOM_FACTOR.powi(steps);
+        let size = (self.cell_size as f64 * scale).round() as u32;
+        let size = size.clamp(self.min_cell_size, self.max_cell_size);
+        if size == self.cell_size {
+            return;
+        }
+        let ratio = size as f64 / self.cell_size as f64;
+        let x = anchor.x() as f64 - (anchor.x() - self.origin.x()) as f64 * ratio;
+        let y = anchor.y() as f64 - (anchor.y() - self.origin.y()) as f64 * ratio;
+        self.cell_size = size;
+        self.origin = Point::new(x.round() as i32, y.round() as i32);
+        self.clamp();
+    }
+
+    // Moves the board by given amount of pixels
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.origin = self.origin.offset(dx, dy);
+        self.clamp();
+    }
+
+    pub fn center_on(&mut self, row: usize, col: usize) {
+        let size = self.cell_size as i32;
+        let x = self.viewport.0 as i32 / 2 - col as i32 * size - size / 2;
+        let y = self.viewport.1 as i32 / 2 - row as i32 * size - size / 2;
+        self.origin = Point::new(x, y);
+        self.clamp();
+    }
+
+    // Pans as little as possible to show the whole cell
+    pub fn ensure_visible(&mut self, row: usize, col: usize) {
+        let frame = self.cell_frame(row, col);
+        let shift = |start: i32, end: i32, viewport: u32| {
+            if start < 0 {
+                -start
+            } else {
+                (viewport as i32 - end).min(0)
+            }
+        };
+        let dx = shift(frame.left(), frame.right(), self.viewport.0);
+        let dy = shift(frame.top(), frame.bottom(), self.viewport.1);
+        self.pan(dx, dy);
+    }
+
+    pub fn cell_at(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        let x = x - self.origin.x();
+        let y = y - self.origin.y();
+        if x < 0 || y < 0 {
+            return None;
+        }
+        let row = (y as u32 / self.cell_size) as usize;
+        let x = x - self.row_shift(row);
+        if x < 0 {
+            return None;
+        }
+        let col = (x as u32 / self.cell_size) as usize;
+        let size = (self.cell_size, self.cell_size);
+        // the gaps between hexagons belong to no cell
+        let is_gap = self.is_hex
+            && !is_in_hexagon(size, x % self.cell_size as i32, y % self.cell_size as i32);
+        (row < self.board.0 && col < self.board.1 && !is_gap).then_some((row, col))
+    }
+
+    pub fn cell_frame(&self, row: usize, col: usize) -> Rect {
+        self.margin_frame(row, col as isize)
+    }
+
+    // Frame of a column beyond the board edges as well
+    pub fn margin_frame(&self, row: usize, col: isize) -> Rect {
+        let size = self.cell_size;
+        Rect::new(
+            self.origin.x() + col as i32 * size as i32 + self.row_shift(row),
+            self.origin.y() + (row as u32 * size) as i32,
+            size,
+            size,
+        )
+    }
+
+    // Visible margin columns, negative ones to the left of the board
+    pub fn visible_margin_columns(&self) -> Vec<isize> {
+        let size = self.cell_size as i32;
+        let first = (-self.origin.x() - self.row_shift(1)).div_euclid(size) as isize;
+        let last = (self.viewport.0 as i32 - self.origin.x() + size - 1).div_euclid(size) as isize;
+        let (cols, margin) = (self.board.1 as isize, self.margin as isize);
+        (-margin..0)
+            .chain(cols..cols + margin)
+            .filter(|col| (first..last).contains(col))
+            .collect()
+    }
+
+    // Rows and columns of cells at least partially visible in the window
+    pub fn visible_cells(&self) -> (Range<usize>, Range<usize>) {
+        let size = self.cell_size as i32;
+        let range = |origin: i32, shift: i32, viewport: u32, count: usize| {
+            let first = (-origin - shift).max(0) / size;
+            let last = (viewport as i32 - origin + size - 1) / size;
+            (first as usize).min(count)..(last.max(0) as usize).min(count)
+        };
+        (
+            range(self.origin.y(), 0, self.viewport.1, self.board.0),
+            range(
+                self.origin.x(),
+                self.row_shift(1),
+                self.viewport.0,
+                self.board.1,
+            ),
+        )
+    }
+
+    // Board overview in the bottom right corner, only when the board doesn't fit
+    pub fn minimap_frame(&self) -> Option<Rect> {
+        if self.is_board_visible() {
+            return None;
+        }
+        let side = self.viewport.0.min(self.viewport.1) as f64 / 4.0;
+        let scale = side / self.board.0.max(self.board.1) as f64;
+        let width = ((self.board.1 as f64 * scale) as u32).max(1);
+        let height = ((self.board.0 as f64 * scale) as u32).max(1);
+        Some(Rect::new(
+            self.viewport.0 as i32 - width as i32 - MINIMAP_MARGIN,
+            self.viewport.1 as i32 - height as i32 - MINIMAP_MARGIN,
+            width,
+            height,
+        ))
+    }
+
+    // Part of the minimap currently visible in the window
+    pub fn minimap_viewport(&self, minimap: Rect) -> Rect {
+        let (width, height) = self.board_pixel_size();
+        let scale_x = minimap.width() as f64 / width as f64;
+        let scale_y = minimap.height() as f64 / height as f64;
+        let x = (-self.origin.x()).max(0) as f64 * scale_x;
+        let y = (-self.origin.y()).max(0) as f64 * scale_y;
+        let w = (self.viewport.0 as f64 * scale_x).min(minimap.width() as f64 - x);
+        let h = (self.viewport.1 as f64 * scale_y).min(minimap.height() as f64 - y);
+        Rect::new(
+            minimap.x() + x as i32,
+            minimap.y() + y as i32,
+            (w as u32).max(1),
+            (h as u32).max(1),
+        )
+    }
+
+    pub fn minimap_cell_at(&self, minimap: Rect, x: i32, y: i32) -> Option<(usize, usize)> {
+        if !minimap.contains_point((x, y)) {
+            return None;
+        }
+        let row = (y - minimap.y()) as usize * self.board.0 / minimap.height() as usize;
+        let col = (x - minimap.x()) as usize * self.board.1 / minimap.width() as usize;
+        Some((row.min(self.board.0 - 1), col.min(self.board.1 - 1)))
+    }
+
+    fn board_pixel_size(&self) -> (i64, i64) {
+        let size = self.cell_size as i64;
+        let shift = self.is_shifted() as i64 * size / 2;
+        (
+            self.board.1 as i64 * size + shift,
+            self.board.0 as i64 * size,
+        )
+    }
+
+    // Boards of a single row are not shifted
+    fn is_shifted(&self) -> bool {
+        self.is_hex && self.board.0 > 1
+    }
+
+    fn row_shift(&self, row: usize) -> i32 {
+        if self.is_hex && row % 2 == 1 {
+            self.cell_size as i32 / 2
+        } else {
+            0
+        }
+    }
+
+    // Board with its margins
+    fn content_pixel_size(&self) -> (i64, i64) {
+        let (width, height) = self.board_pixel_size();
+        (
+            width + 2 * (self.margin as i64 * self.cell_size as i64),
+            height,
+        )
+    }
+
+    // Centers the board along the axis it fits, otherwise forbids panning beyond its edges
+    fn clamp(&mut self) {
+        let (width, height) = self.content_pixel_size();
+        let margin = (self.margin as u32 * self.cell_size) as i32;
+        let axis = |origin: i32, viewport: u32, board: i64| {
+            let viewport = viewport as i64;
+            let value = if board <= viewport {
+                (viewport - board) / 2
+            } else {
+                (origin as i64).clamp(viewport - board, 0)
+            };
+            value as i32
+        };
+        self.origin = Point::new(
+            axis(self.origin.x() - margin, self.viewport.0, width) + margin,
+            axis(self.origin.y(), self.viewport.1, height),
+        );
+    }
+}
+
+// Camera over a board without edges, panning and zooming are not limited
+pub struct UnboundedCamera {
+    min_cell_size: u32,
+    max_cell_size: u32,
+    cell_size: u32,
+    // window position of the top left corner of cell (0, 0)
+    origin: (i64, i64),
+    viewport: (u32, u32),
+}
+
+impl UnboundedCamera {
+    pub fn new(min_cell_size: u32, max_cell_size: u32, cell_size: u32) -> Self {
+        Self {
+            min_cell_size,
+            max_cell_size: max_cell_size.max(min_cell_size),
+            cell_size: cell_size.clamp(min_cell_size, max_cell_size.max(min_cell_size)),
+            origin: (0, 0),
+            viewport: (0, 0),
+        }
+    }
+
+    // Keeps the cell in the middle of the window in place
+    pub fn set_viewport(&mut self, viewport: (u32, u32)) {
+        let dx = (viewport.0 as i64 - self.viewport.0 as i64) / 2;
+        let dy = (viewport.1 as i64 - self.viewport.1 as i64) / 2;
+        self.origin = (self.origin.0 + dx, self.origin.1 + dy);
+        self.viewport = viewport;
+    }
+
+    #[cfg(test)]
+    pub fn cell_size(&self) -> u32 {
+        self.cell_size
+    }
+
+    // Positive steps zoom in, the point under the anchor stays in place
+    pub fn zoom(&mut self, steps: i32, anchor: Point) {
+        let scale = ZOOM_FACTOR.powi(steps);
+        let size = (self.cell_size as f64 * scale).round() as u32;
+        let size = size.clamp(self.min_cell_size, self.max_cell_size);
+        let ratio = size as f64 / self.cell_size as f64;
+        let (x, y) = (anchor.x() as f64, anchor.y() as f64);
+        self.origin = (
+            (x - (x - self.origin.0 as f64) * ratio).round() as i64,
+            (y - (y - self.origin.1 as f64) * ratio).round() as i64,
+        );
+        self.cell_size = size;
+    }
+
+    // Moves the board by given amount of pixels
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.origin = (self.origin.0 + dx as i64, self.origin.1 + dy as i64);
+    }
+
+    pub fn center_on(&mut self, row: i64, col: i64) {
+        let size = self.cell_size as i64;
+        self.origin = (
+            self.viewport.0 as i64 / 2 - col * size - size / 2,
+            self.viewport.1 as i64 / 2 - row * size - size / 2,
+        );
+    }
+
+    // Pans as little as possible to have the cell at least `margin` cells away from the edges
+    pub fn ensure_visible(&mut self, row: i64, col: i64, margin: u32) {
+        let size = self.cell_size as i64;
+        let margin = margin as i64 * size;
+        let shift = |start: i64, viewport: u32| {
+            let end = start + size;
+            let viewport = viewport as i64;
+            if viewport < 2 * margin + size {
+                viewport / 2 - start - size / 2
+            } else if start < margin {
+                margin - start
+            } else {
+                (viewport - margin - end).min(0)
+            }
+        };
+        let frame = self.cell_frame(row, col);
+        let dx = shift(frame.x() as i64, self.viewport.0);
+        let dy = shift(frame.y() as i64, self.viewport.1);
+        self.origin = (self.origin.0 + dx, self.origin.1 + dy);
+    }
+
+    pub fn cell_at(&self, x: i32, y: i32) -> (i64, i64) {
+        let size = self.cell_size as i64;
+        (
+            (y as i64 - self.origin.1).div_euclid(size),
+            (x as i64 - self.origin.0).div_euclid(size),
+        )
+    }
+
+    pub fn cell_frame(&self, row: i64, col: i64) -> Rect {
+        let size = self.cell_size;
+        Rect::new(
+            (self.origin.0 + col * size as i64) as i32,
+            (self.origin.1 + row * size as i64) as i32,
+            size,
+            size,
+        )
+    }
+
+    // Rows and columns of cells at least partially visible in the window
+    pub fn visible_cells(&self) -> (Range<i64>, Range<i64>) {
+        let (first_row, first_col) = self.cell_at(0, 0);
+        let (last_row, last_col) =
+            self.cell_at(self.viewport.0 as i32 - 1, self.viewport.1 as i32 - 1);
+        (first_row..last_row + 1, first_col..last_col + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_camera(viewport: (u32, u32), board: (usize, usize)) -> Camera {
+        let mut camera = Camera::new(16, 128);
+        camera.fit(viewport, board);
+        camera
+    }
+
+    #[test]
+    fn camera_fit_centers_board() {
+        let camera = create_camera((400, 400), (4, 8));
+        assert_eq!(camera.cell_size(), 50);
+        assert_eq!(camera.cell_frame(0, 0), Rect::new(0, 100, 50, 50));
+        assert_eq!(camera.visible_cells(), (0..4, 0..8));
+        assert!(camera.minimap_frame().is_none());
+    }
+
+    #[test]
+    fn camera_visible_cells_of_huge_board() {
+        let mut camera = create_camera((800, 600), (200, 200));
+        assert_eq!(camera.cell_size(), 16);
+        assert_eq!(camera.visible_cells(), (0..38, 0..50));
+        camera.pan(-8, -1000);
+        assert_eq!(camera.visible_cells(), (62..100, 0..51));
+        assert_eq!(camera.cell_at(0, 0), Some((62, 0)));
+        camera.pan(10_000, 10_000);
+        assert_eq!(camera.cell_frame(0, 0), Rect::new(0, 0, 16, 16));
+    }
+
+    #[test]
+    fn camera_zoom_keeps_anchor() {
+        let mut camera = create_camera((800, 600), (200, 200));
+        camera.pan(-1600, -1600);
+        let anchor = Point::new(400, 300);
+        let before = camera.cell_at(anchor.x(), anchor.y());
+        camera.zoom(3, anchor);
+        assert_eq!(camera.cell_size(), 31);
+        assert_eq!(camera.cell_at(anchor.x(), anchor.y()), before);
+        camera.zoom(-20, anchor);
+        assert_eq!(camera.cell_size(), 16);
+    }
+
+    #[test]
+    fn camera_minimap() {
+        let mut camera = create_camera((800, 600), (200, 100));
+        let minimap = camera.minimap_frame().unwrap();
+        assert_eq!(minimap, Rect::new(717, 442, 75, 150));
+        assert_eq!(camera.minimap_cell_at(minimap, 717, 442), Some((0, 0)));
+        assert_eq!(camera.minimap_cell_at(minimap, 0, 0), None);
+        assert_eq!(
+            camera.minimap_viewport(minimap),
+            Rect::new(717, 442, 37, 28)
+        );
+        camera.center_on(100, 50);
+        assert_eq!(camera.cell_at(400, 300), Some((100, 50)));
+    }
+
+    #[test]
+    fn camera_ensure_visible() {
+        let mut camera = create_camera((800, 600), (200, 200));
+        camera.ensure_visible(10, 10);
+        assert_eq!(camera.cell_frame(0, 0), Rect::new(0, 0, 16, 16));
+        camera.ensure_visible(40, 60);
+        assert_eq!(camera.cell_frame(40, 60), Rect::new(784, 584, 16, 16));
+        camera.ensure_visible(0, 5);
+        assert_eq!(camera.cell_frame(0, 5), Rect::new(0, 0, 16, 16));
+    }
+
+    #[test]
+    fn camera_unbounded() {
+        let mut camera = UnboundedCamera::new(16, 128, 40);
+        camera.set_viewport((400, 300));
+        camera.center_on(0, 0);
+        assert_eq!(camera.cell_frame(0, 0), Rect::new(180, 130, 40, 40));
+        assert_eq!(camera.cell_at(179, 129), (-1, -1));
+        assert_eq!(camera.visible_cells(), (-4..5, -5..6));
+        camera.pan(-10_000, 0);
+        assert_eq!(camera.cell_at(200, 150), (0, 250));
+
+        camera.ensure_visible(0, 260, 1);
+        assert_eq!(camera.cell_frame(0, 260), Rect::new(320, 130, 40, 40));
+        camera.ensure_visible(-10, 260, 1);
+        assert_eq!(camera.cell_frame(-10, 260).y(), 40);
+
+        let anchor = Point::new(100, 100);
+        let before = camera.cell_at(anchor.x(), anchor.y());
+        camera.zoom(2, anchor);
+        assert_eq!(camera.cell_size(), 63);
+        assert_eq!(camera.cell_at(anchor.x(), anchor.y()), before);
+    }
+
+    #[test]
+    fn camera_margin_columns() {
+        let mut camera = Camera::new(10, 100);
+        camera.set_margin(2);
+        camera.fit((400, 400), (4, 4));
+        // eight columns fit, the board is centered between the margins
+        assert_eq!(camera.cell_size(), 50);
+        assert_eq!(camera.cell_frame(0, 0), Rect::new(100, 100, 50, 50));
+        assert_eq!(camera.visible_margin_columns(), vec![-2, -1, 4, 5]);
+        assert_eq!(camera.margin_frame(0, -2).x(), 0);
+        assert_eq!(camera.cell_at(60, 120), None);
+
+        // panning stops at the outer edge of the margin
+        camera.zoom(2, Point::new(0, 0));
+        camera.pan(1000, 0);
+        assert_eq!(camera.margin_frame(0, -2).x(), 0);
+        assert_eq!(camera.visible_margin_columns(), vec![-2, -1]);
+    }
+
+    #[test]
+    fn camera_hex_rows() {
+        let mut camera = Camera::new(10, 100);
+        camera.set_hex(true);
+        // four and a half cells fill the width
+        camera.fit((450, 300), (3, 4));
+        assert_eq!(camera.cell_size(), 100);
+        assert_eq!(camera.cell_frame(1, 0), Rect::new(50, 100, 100, 100));
+        assert_eq!(camera.cell_frame(2, 3), Rect::new(300, 200, 100, 100));
+        assert_eq!(camera.cell_at(40, 120), None);
+        assert_eq!(camera.cell_at(60, 120), Some((1, 0)));
+        assert_eq!(camera.cell_at(440, 120), Some((1, 3)));
+        assert_eq!(camera.cell_at(440, 20), None);
+        assert_eq!(camera.cell_at(52, 102), None);
+        assert_eq!(camera.cell_at(148, 198), None);
+        assert_eq!(camera.visible_cells(), (0..3, 0..4));
+    }
+
+    #[test]
+    fn hexagon_points() {
+        let size = (64, 64);
+        assert!(is_in_hexagon(size, 32, 0) && is_in_hexagon(size, 0, 32));
+        assert!(is_in_hexagon(size, 63, 40) && is_in_hexagon(size, 32, 63));
+        assert!(!is_in_hexagon(size, 0, 0) && !is_in_hexagon(size, 63, 63));
+        assert!(!is_in_hexagon(size, 0, 63) && !is_in_hexagon(size, 64, 32));
+    }
+}
@@ -1,19 +1,363 @@
-pub struct Configuration;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::bindings::KeyBindings;
+use crate::game_model::{check_board, Grid, Topology, MAX_CELL_MINES};
+use crate::key_value;
+
+pub struct Configuration {
+    row_count: usize,
+    col_count: usize,
+    mines_count: usize,
+    cell_mines: usize,
+    // shape of the board replacing its rows and columns
+    mask: Option<PathBuf>,
+    theme: String,
+    volume: u8,
+    is_muted: bool,
+    animations: bool,
+    is_first_click_safe: bool,
+    grid: Grid,
+    topology: Topology,
+    ghost_columns: usize,
+    endless_density: f64,
+    layers: usize,
+    layered_mines: usize,
+    bindings: KeyBindings,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            row_count: 10,
+            col_count: 16,
+            mines_count: 20,
+            cell_mines: 1,
+            mask: None,
+            theme: "classic".to_string(),
+            volume: 80,
+            is_muted: false,
+            animations: true,
+            is_first_click_safe: true,
+            grid: Grid::Square,
+            topology: Topology::Bounded,
+            ghost_columns: 2,
+            endless_density: 0.2,
+            layers: 4,
+            layered_mines: 60,
+            bindings: KeyBindings::default(),
+        }
+    }
+}
 
 impl Configuration {
+    // Missing file means default configuration
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        for (key, value) in key_value::parse(text)? {
+            match key.as_str() {
+                "difficulty" => {
+                    let difficulty = value.parse::<Difficulty>()?;
+                    config.row_count = difficulty.row_count();
+                    config.col_count = difficulty.col_count();
+                    config.mines_count = difficulty.mines_count();
+                }
+                "rows" => config.row_count = parse_value(&key, &value)?,
+                "cols" => config.col_count = parse_value(&key, &value)?,
+                "mines" => config.mines_count = parse_value(&key, &value)?,
+                "cell_mines" => config.cell_mines = parse_value(&key, &value)?,
+                "mask" => config.mask = Some(PathBuf::from(value)),
+                "theme" => config.theme = value,
+                "volume" => config.volume = parse_value(&key, &value)?,
+                "mute" => config.is_muted = parse_value(&key, &value)?,
+                "animations" => config.animations = parse_value(&key, &value)?,
+                "first_click_safe" => config.is_first_click_safe = parse_value(&key, &value)?,
+                "grid" => config.grid = value.parse()?,
+                "topology" => config.topology = value.parse()?,
+                "ghost_columns" => config.ghost_columns = parse_value(&key, &value)?,
+                "endless_density" => config.endless_density = parse_value(&key, &value)?,
+                "layers" => config.layers = parse_value(&key, &value)?,
+                "layered_mines" => config.layered_mines = parse_value(&key, &value)?,
+                _ if key.starts_with("key.") => config.bindings.parse_entry(&key[4..], &value)?,
+                _ => return Err(format!("Unknown key '{key}'")),
+            }
+        }
+        if config.row_count == 0 || config.col_count == 0 {
+            return Err("Board can't be empty".to_string());
+        }
+        if !(1..=MAX_CELL_MINES).contains(&config.cell_mines) {
+            return Err(format!("Mines per cell should be in 1..={MAX_CELL_MINES}"));
+        }
+        // the mask replaces rows and cols, its size is checked once it's loaded
+        if config.mask.is_none() {
+            check_board(
+                config.row_count * config.col_count,
+                config.row_count,
+                config.mines_count,
+                config.cell_mines,
+                config.grid,
+                config.topology,
+            )?;
+        }
+        if config.volume > 100 {
+            return Err("Volume should be in 0..=100".to_string());
+        }
+        if !(config.endless_density > 0.0 && config.endless_density < 1.0) {
+            return Err("Endless density should be between 0 and 1".to_string());
+        }
+        if config.layers == 0 {
+            return Err("Layered board needs at least one layer".to_string());
+        }
+        Ok(config)
+    }
+
     pub fn window_title(&self) -> String {
         "Minesweeper".to_string()
     }
 
     pub fn row_count(&self) -> usize {
-        10
+        self.row_count
     }
 
     pub fn col_count(&self) -> usize {
+        self.col_count
+    }
+
+    pub fn mines_count(&self) -> usize {
+        self.mines_count
+    }
+
+    // Most mines a single cell may hold
+    pub fn cell_mines(&self) -> usize {
+        self.cell_mines
+    }
+
+    pub fn mask(&self) -> Option<&Path> {
+        self.mask.as_deref()
+    }
+
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    // Sound volume in percents
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.is_muted
+    }
+
+    pub fn animations(&self) -> bool {
+        self.animations
+    }
+
+    pub fn is_first_click_safe(&self) -> bool {
+        self.is_first_click_safe
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.grid
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    // Wrapped columns shown at each side of a joined board
+    pub fn ghost_columns(&self) -> usize {
+        self.ghost_columns
+    }
+
+    // Share of mines among cells of the endless mode
+    pub fn endless_density(&self) -> f64 {
+        self.endless_density
+    }
+
+    // Depth of the board in the 3D mode, rows and columns are shared with the classic game
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+
+    pub fn layered_mines(&self) -> usize {
+        self.layered_mines
+    }
+
+    pub fn bindings(&self) -> &KeyBindings {
+        &self.bindings
+    }
+
+    pub fn cell_size(&self) -> u32 {
+        64
+    }
+
+    pub fn min_cell_size(&self) -> u32 {
         16
     }
 
+    pub fn max_cell_size(&self) -> u32 {
+        128
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Difficulty {
+    pub fn row_count(&self) -> usize {
+        match self {
+            Difficulty::Beginner => 9,
+            Difficulty::Intermediate => 16,
+            Difficulty::Expert => 16,
+        }
+    }
+
+    pub fn col_count(&self) -> usize {
+        match self {
+            Difficulty::Beginner => 9,
+            Difficulty::Intermediate => 16,
+            Difficulty::Expert => 30,
+        }
+    }
+
     pub fn mines_count(&self) -> usize {
-        20
+        match self {
+            Difficulty::Beginner => 10,
+            Difficulty::Intermediate => 40,
+            Difficulty::Expert => 99,
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "beginner" => Ok(Difficulty::Beginner),
+            "intermediate" => Ok(Difficulty::Intermediate),
+            "expert" => Ok(Difficulty::Expert),
+            _ => Err(format!("Unknown difficulty '{s}'")),
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("Invalid value '{value}' of '{key}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::KeyAction;
+    use sdl2::keyboard::Keycode;
+
+    #[test]
+    fn config_parse() {
+        let config = Configuration::parse("rows = 5\ncols = 6\nmines = 7\ntheme = dark").unwrap();
+        assert_eq!(config.row_count(), 5);
+        assert_eq!(config.col_count(), 6);
+        assert_eq!(config.mines_count(), 7);
+        assert_eq!(config.theme(), "dark");
+
+        let config = Configuration::parse("difficulty = Expert").unwrap();
+        assert_eq!(config.col_count(), 30);
+        assert_eq!(config.theme(), "classic");
+        assert_eq!(config.volume(), 80);
+        assert!(!config.is_muted());
+        assert!(config.animations());
+        assert!(config.is_first_click_safe());
+
+        let config = Configuration::parse("volume = 35\nmute = true\nanimations = false").unwrap();
+        assert_eq!(config.volume(), 35);
+        assert!(config.is_muted());
+        assert!(!config.animations());
+
+        let config = Configuration::parse("first_click_safe = false").unwrap();
+        assert!(!config.is_first_click_safe());
+
+        let config = Configuration::parse("topology = toroidal\nghost_columns = 0").unwrap();
+        assert_eq!(config.topology(), Topology::Toroidal);
+        assert_eq!(config.ghost_columns(), 0);
+        assert_eq!(Configuration::default().topology(), Topology::Bounded);
+
+        let config = Configuration::parse("grid = hex").unwrap();
+        assert_eq!(config.grid(), Grid::Hex);
+        let config = Configuration::parse("grid = radius2").unwrap();
+        assert_eq!(config.grid(), Grid::Radius2);
+
+        let config = Configuration::parse("rows = 2\ncols = 2\nmines = 9\ncell_mines = 3").unwrap();
+        assert_eq!(config.cell_mines(), 3);
+        assert_eq!(Configuration::default().cell_mines(), 1);
+
+        let config = Configuration::parse("mask = masks/heart.png").unwrap();
+        assert_eq!(config.mask(), Some(Path::new("masks/heart.png")));
+        assert_eq!(Configuration::default().mask(), None);
+        // rows and cols don't limit the mines or the grid of a masked board
+        let config = Configuration::parse(
+            "mask = circle.txt\nmines = 170\ngrid = hex\ntopology = toroidal\nrows = 9",
+        )
+        .unwrap();
+        assert_eq!(config.mines_count(), 170);
+
+        let config = Configuration::parse("endless_density = 0.15").unwrap();
+        assert_eq!(config.endless_density(), 0.15);
+        assert_eq!(Configuration::default().endless_density(), 0.2);
+
+        let config = Configuration::parse("layers = 6\nlayered_mines = 100").unwrap();
+        assert_eq!((config.layers(), config.layered_mines()), (6, 100));
+
+        let config = Configuration::parse("key.open = Space\nkey.restart = F2").unwrap();
+        assert_eq!(
+            config.bindings().action(Keycode::Space),
+            Some(KeyAction::Open)
+        );
+        assert_eq!(
+            config.bindings().action(Keycode::F2),
+            Some(KeyAction::Restart)
+        );
+    }
+
+    #[test]
+    fn config_parse_errors() {
+        assert!(Configuration::parse("rows = many").is_err());
+        assert!(Configuration::parse("size = 5").is_err());
+        assert!(Configuration::parse("rows = 2\ncols = 2\nmines = 4").is_err());
+        assert!(Configuration::parse("rows = 2\ncols = 2\nmines = 10\ncell_mines = 3").is_err());
+        assert!(Configuration::parse("cell_mines = 0").is_err());
+        assert!(Configuration::parse("cell_mines = 8").is_err());
+        assert!(Configuration::parse("volume = 101").is_err());
+        assert!(Configuration::parse("mute = yes").is_err());
+        assert!(Configuration::parse("topology = flat").is_err());
+        assert!(Configuration::parse("grid = triangle").is_err());
+        assert!(Configuration::parse("grid = hex\ntopology = toroidal\nrows = 9").is_err());
+        assert!(Configuration::parse("endless_density = 1").is_err());
+        assert!(Configuration::parse("endless_density = 0").is_err());
+        assert!(Configuration::parse("layers = 0").is_err());
+        assert!(Configuration::parse("key.jump = J").is_err());
+        assert!(Configuration::parse("key.open = Ctrl").is_err());
+    }
+
+    #[test]
+    fn config_load_missing_file() {
+        let config = Configuration::load(Path::new("no/such/file.cfg")).unwrap();
+        assert_eq!(config.row_count(), Configuration::default().row_count());
     }
 }
@@ -0,0 +1,486 @@
+use std::path::PathBuf;
+
+use sdl2::rect::Point;
+
+use crate::audio::{AudioSink, NoAudio, Sound};
+use crate::bindings::{KeyAction, KeyBindings};
+use crate::camera::UnboundedCamera;
+use crate::game_model::endless::{self, Coordinate, EndlessEvent, EndlessModel};
+use crate::game_model::State;
+use crate::minesweeper::Size;
+use crate::render::{InputEvent, InputSource, Renderer, Sprite};
+use crate::resources::ColorManager;
+use crate::screen::{self, *};
+
+// Cells kept between the last opened cell and the window edge
+const FOLLOW_MARGIN: u32 = 3;
+
+// Screen of the endless mode, the board has no edges and any drag pans it
+pub struct Endless<R: Renderer, I: InputSource> {
+    model: EndlessModel,
+    renderer: R,
+    input: I,
+    color_manager: ColorManager,
+    is_running: bool,
+    camera: UnboundedCamera,
+    window_size: Size,
+    pointer: Pointer<Coordinate>,
+    screenshot_path: Option<PathBuf>,
+    audio: Box<dyn AudioSink>,
+    is_muted: bool,
+    bindings: KeyBindings,
+    // keyboard selected cell, shown after it's moved by keys
+    cursor: Coordinate,
+    is_cursor_visible: bool,
+}
+
+impl<R: Renderer, I: InputSource> Endless<R, I> {
+    pub fn new(
+        model: EndlessModel,
+        renderer: R,
+        input: I,
+        color_manager: ColorManager,
+        mut camera: UnboundedCamera,
+        window_size: Size,
+    ) -> Self {
+        camera.set_viewport((window_size.width, window_size.height));
+        camera.center_on(0, 0);
+        Self {
+            model,
+            renderer,
+            input,
+            color_manager,
+            is_running: false,
+            camera,
+            window_size,
+            pointer: Pointer::new(),
+            screenshot_path: None,
+            audio: Box::new(NoAudio),
+            is_muted: false,
+            bindings: KeyBindings::default(),
+            cursor: (0, 0),
+            is_cursor_visible: false,
+        }
+    }
+
+    pub fn set_audio(&mut self, audio: Box<dyn AudioSink>, is_muted: bool) {
+        self.audio = audio;
+        self.is_muted = is_muted;
+    }
+
+    pub fn set_bindings(&mut self, bindings: KeyBindings) {
+        self.bindings = bindings;
+    }
+
+    fn play(&mut self, sound: Sound) {
+        if !self.is_muted {
+            self.audio.play(sound);
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), String> {
+        self.is_running = true;
+        screen::run(self)
+    }
+
+    pub fn handle_events(&mut self) {
+        for event in self.input.poll_events() {
+            match event {
+                InputEvent::Quit => self.is_running = false,
+                InputEvent::KeyDown(keycode) => {
+                    if let Some(action) = self.bindings.action(keycode) {
+                        self.handle_action(action);
+                    }
+                }
+                InputEvent::Resized(width, height) => {
+                    self.window_size = Size { height, width };
+                    self.camera.set_viewport((width, height));
+                }
+                InputEvent::MouseWheel(steps) => {
+                    let state = self.input.mouse_state();
+                    self.camera.zoom(steps, Point::new(state.x, state.y));
+                }
+            }
+        }
+
+        let state = self.input.mouse_state();
+        let cell = self.camera.cell_at(state.x, state.y);
+        let camera = &mut self.camera;
+        let press = self.pointer.update(state, Some(cell), true, |delta| {
+            camera.pan(delta.x(), delta.y())
+        });
+        if let Some(press) = press {
+            if let (Some(cell), Some(action)) = (press.cell, press.action()) {
+                // keyboard continues from the clicked cell
+                self.cursor = cell;
+                self.is_cursor_visible = false;
+                self.act(action, cell);
+            }
+        }
+    }
+
+    // Keys of the classic game without meaning here are ignored
+    fn handle_action(&mut self, action: KeyAction) {
+        match action {
+            KeyAction::Quit => self.is_running = false,
+            KeyAction::Restart => {
+                self.model = EndlessModel::new(rand::random(), self.model.density());
+                self.camera.center_on(0, 0);
+                self.cursor = (0, 0);
+                self.is_cursor_visible = false;
+                self.pointer.cancel();
+                println!("Endless seed: {}", self.model.seed());
+            }
+            KeyAction::Screenshot => {
+                let path = format!("screenshot_{}.png", timestamp());
+                self.screenshot_path = Some(PathBuf::from(path));
+            }
+            KeyAction::Mute => self.is_muted = !self.is_muted,
+            KeyAction::Up => self.move_cursor(-1, 0),
+            KeyAction::Down => self.move_cursor(1, 0),
+            KeyAction::Left => self.move_cursor(0, -1),
+            KeyAction::Right => self.move_cursor(0, 1),
+            KeyAction::NextCovered => self.move_cursor_to_covered(),
+            KeyAction::Open | KeyAction::Flag | KeyAction::Chord => {
+                self.is_cursor_visible = true;
+                self.act(action, self.cursor);
+            }
+            _ => {}
+        }
+    }
+
+    // The first key press only shows the cursor
+    fn move_cursor(&mut self, rows: i64, cols: i64) {
+        if self.is_cursor_visible {
+            self.cursor = (self.cursor.0 + rows, self.cursor.1 + cols);
+        }
+        self.show_cursor();
+    }
+
+    // Next covered and not flagged cell of the window in reading order, wrapping at its end
+    fn move_cursor_to_covered(&mut self) {
+        let (rows, cols) = self.camera.visible_cells();
+        let is_visible = rows.contains(&self.cursor.0) && cols.contains(&self.cursor.1);
+        let width = cols.end - cols.start;
+        let count = (rows.end - rows.start) * width;
+        let start = if is_visible {
+            (self.cursor.0 - rows.start) * width + self.cursor.1 - cols.start
+        } else {
+            count - 1
+        };
+        let next = (1..=count)
+            .map(|offset| (start + offset) % count)
+            .map(|index| (rows.start + index / width, cols.start + index % width))
+            .find(|&(row, col)| {
+                let cell = self.model.get_cell(row, col);
+                !cell.is_visible() && !cell.is_flagged()
+            });
+        if let Some(cell) = next {
+            self.cursor = cell;
+        }
+        self.show_cursor();
+    }
+
+    fn show_cursor(&mut self) {
+        self.is_cursor_visible = true;
+        self.camera.ensure_visible(self.cursor.0, self.cursor.1, 0);
+    }
+
+    // Open, flag or chord of the cell by the mouse or the keyboard
+    fn act(&mut self, action: KeyAction, (row, col): Coordinate) {
+        let events = match action {
+            KeyAction::Open => self.model.open_cell(row, col),
+            KeyAction::Flag => self.model.flag_cell(row, col),
+            KeyAction::Chord => self.model.chord(row, col),
+            _ => return,
+        };
+        if matches!(events.first(), Some(EndlessEvent::CellsRevealed(_))) {
+            // the camera follows the exploration front
+            self.camera.ensure_visible(row, col, FOLLOW_MARGIN);
+        }
+        self.handle_model_events(events);
+    }
+
+    fn handle_model_events(&mut self, events: Vec<EndlessEvent>) {
+        for event in events {
+            let sound = match event {
+                EndlessEvent::CellsRevealed(cells) if cells.len() > 1 => Sound::Cascade,
+                EndlessEvent::CellsRevealed(_) => Sound::Reveal,
+                EndlessEvent::Flagged(..) => Sound::Flag,
+                EndlessEvent::Unflagged(..) => Sound::Unflag,
+                EndlessEvent::Exploded(..) => Sound::Explosion,
+            };
+            self.play(sound);
+        }
+    }
+
+    pub fn draw(&mut self) -> Result<(), String> {
+        self.renderer.clear();
+        let is_lose = self.model.state() == State::Lose;
+        let pressed = self.pressed_cells();
+        let (rows, cols) = self.camera.visible_cells();
+        for row in rows {
+            for col in cols.clone() {
+                let sprite = if pressed.contains(&(row, col)) {
+                    Sprite::Empty
+                } else {
+                    let is_exploded = self.model.is_exploded(row, col);
+                    cell_sprite(self.model.get_cell(row, col), is_lose, is_exploded)
+                };
+                self.renderer
+                    .draw_sprite(sprite, self.camera.cell_frame(row, col))?;
+            }
+        }
+        if let Some((row, col)) = self.highlighted_cell() {
+            let frame = self.camera.cell_frame(row, col);
+            self.renderer.fill_rect(self.color_manager.hover(), frame)?;
+        }
+        if self.is_cursor_visible {
+            let frame = self.camera.cell_frame(self.cursor.0, self.cursor.1);
+            draw_frame(&mut self.renderer, self.color_manager.cursor(), frame)?;
+        }
+        if is_lose {
+            show_message(
+                &mut self.renderer,
+                &self.color_manager,
+                &self.window_size,
+                "Game Over",
+            )?;
+        }
+        let status = format!("Cleared: {}", self.model.score());
+        show_status(
+            &mut self.renderer,
+            &self.color_manager,
+            &self.window_size,
+            &status,
+        )?;
+        if let Some(path) = self.screenshot_path.take() {
+            self.renderer.save_screenshot(&path)?;
+            println!("Screenshot saved to {}", path.display());
+        }
+        self.renderer.present();
+        Ok(())
+    }
+
+    // Mouse feedback is given only to the game in progress
+    fn is_board_interactive(&self) -> bool {
+        self.model.state() == State::InProgress && !self.pointer.is_dragging()
+    }
+
+    fn hovered_cell(&self) -> Coordinate {
+        let position = self.pointer.position();
+        self.camera.cell_at(position.x(), position.y())
+    }
+
+    // Covered cell under the mouse while no button is held
+    fn highlighted_cell(&self) -> Option<Coordinate> {
+        if self.pointer.is_pressed() || !self.is_board_interactive() {
+            return None;
+        }
+        let (row, col) = self.hovered_cell();
+        (!self.model.get_cell(row, col).is_visible()).then_some((row, col))
+    }
+
+    // Covered cells under the held buttons
+    fn pressed_cells(&self) -> Vec<Coordinate> {
+        if !self.is_board_interactive() {
+            return Vec::new();
+        }
+        let mut cells = self
+            .pointer
+            .depressed_cells(Some(self.hovered_cell()), |(row, col)| {
+                endless::neighbours(row, col)
+            });
+        cells.retain(|&(row, col)| {
+            let cell = self.model.get_cell(row, col);
+            !cell.is_visible() && !cell.is_flagged()
+        });
+        cells
+    }
+}
+
+impl<R: Renderer, I: InputSource> Screen for Endless<R, I> {
+    fn handle_events(&mut self) {
+        self.handle_events();
+    }
+
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    fn draw(&mut self) -> Result<(), String> {
+        self.draw()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::RecordingAudio;
+    use crate::render::{DrawCommand, MouseSnapshot, RecordingRenderer, ScriptedInput};
+    use sdl2::keyboard::Keycode;
+    use sdl2::mouse::MouseButton;
+    use std::collections::HashSet;
+
+    const CELL: u32 = 32;
+
+    fn create_game(model: EndlessModel) -> Endless<RecordingRenderer, ScriptedInput> {
+        Endless::new(
+            model,
+            RecordingRenderer::default(),
+            ScriptedInput::default(),
+            ColorManager::default(),
+            UnboundedCamera::new(16, 128, CELL),
+            Size {
+                height: CELL * 11,
+                width: CELL * 15,
+            },
+        )
+    }
+
+    // Window point in the middle of the cell
+    fn cell_center(game: &Endless<RecordingRenderer, ScriptedInput>, row: i64, col: i64) -> Point {
+        game.camera.cell_frame(row, col).center()
+    }
+
+    fn click(
+        game: &mut Endless<RecordingRenderer, ScriptedInput>,
+        button: MouseButton,
+        row: i64,
+        col: i64,
+    ) {
+        let point = cell_center(game, row, col);
+        game.input.mouse.x = point.x();
+        game.input.mouse.y = point.y();
+        game.input.mouse.buttons = HashSet::from([button]);
+        game.handle_events();
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+    }
+
+    fn sprite_at_cell(
+        game: &Endless<RecordingRenderer, ScriptedInput>,
+        row: i64,
+        col: i64,
+    ) -> Option<Sprite> {
+        let point = cell_center(game, row, col);
+        game.renderer.sprite_at(point.x(), point.y())
+    }
+
+    #[test]
+    fn endless_screen_play_and_follow() {
+        let mut game = create_game(EndlessModel::new(5, 0.2));
+        let audio = RecordingAudio::default();
+        let sounds = audio.sounds.clone();
+        game.set_audio(Box::new(audio), false);
+        game.draw().unwrap();
+        // the origin is opened in the middle of the window
+        assert_eq!(
+            game.camera.cell_at(7 * CELL as i32, 5 * CELL as i32),
+            (0, 0)
+        );
+        assert_eq!(sprite_at_cell(&game, 0, 0), Some(Sprite::Empty));
+        assert_eq!(sprite_at_cell(&game, 0, 20), None);
+        assert_eq!(
+            game.renderer.texts(),
+            vec![format!("Cleared: {}", game.model.score())]
+        );
+
+        // a covered safe cell near the right edge of the window
+        let (row, col) = (0..=4)
+            .map(|row| (row, 6))
+            .find(|&(row, col)| {
+                let cell = game.model.get_cell(row, col);
+                cell.is_safe() && !cell.is_visible()
+            })
+            .unwrap();
+        click(&mut game, MouseButton::Left, row, col);
+        assert!(game.model.get_cell(row, col).is_visible());
+        let frame = game.camera.cell_frame(row, col);
+        assert!(frame.right() <= (CELL * (15 - FOLLOW_MARGIN)) as i32);
+        assert_eq!(sounds.borrow().len(), 1);
+
+        // dragging pans without opening
+        let score = game.model.score();
+        let before = game.camera.cell_at(100, 100);
+        game.input.mouse = MouseSnapshot {
+            x: 100,
+            y: 100,
+            buttons: HashSet::from([MouseButton::Left]),
+        };
+        game.handle_events();
+        game.input.mouse.x += 3 * CELL as i32;
+        game.handle_events();
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+        assert_eq!(game.camera.cell_at(100, 100), (before.0, before.1 - 3));
+        assert_eq!(game.model.score(), score);
+
+        game.input
+            .events
+            .push_back(InputEvent::KeyDown(Keycode::Escape));
+        game.handle_events();
+        assert!(!game.is_running);
+    }
+
+    fn press_key(game: &mut Endless<RecordingRenderer, ScriptedInput>, keycode: Keycode) {
+        game.input.events.push_back(InputEvent::KeyDown(keycode));
+        game.handle_events();
+    }
+
+    #[test]
+    fn endless_screen_keyboard_play() {
+        let mut game = create_game(EndlessModel::new(5, 0.2));
+        // the first key press only shows the cursor
+        press_key(&mut game, Keycode::Right);
+        assert!(game.is_cursor_visible);
+        assert_eq!(game.cursor, (0, 0));
+        press_key(&mut game, Keycode::Tab);
+        let (row, col) = game.cursor;
+        assert!(!game.model.get_cell(row, col).is_visible());
+        press_key(&mut game, Keycode::F);
+        assert!(game.model.get_cell(row, col).is_flagged());
+        press_key(&mut game, Keycode::F);
+
+        let (row, col) = (1..=5)
+            .map(|col| (-3, col))
+            .find(|&(row, col)| game.model.get_cell(row, col).is_safe())
+            .unwrap();
+        game.cursor = (row, col);
+        press_key(&mut game, Keycode::Return);
+        assert!(game.model.get_cell(row, col).is_visible());
+        game.draw().unwrap();
+        let cursor = game.color_manager.cursor();
+        assert!(game
+            .renderer
+            .commands
+            .iter()
+            .any(|command| matches!(command, DrawCommand::FillRect(color, _) if *color == cursor)));
+    }
+
+    #[test]
+    fn endless_screen_depressed_cells() {
+        let mut game = create_game(EndlessModel::new(5, 0.2));
+        let (row, col) = (-4..=4)
+            .map(|row| (row, 5))
+            .find(|&(row, col)| !game.model.get_cell(row, col).is_visible())
+            .unwrap();
+        let point = cell_center(&game, row, col);
+        game.input.mouse = MouseSnapshot {
+            x: point.x(),
+            y: point.y(),
+            buttons: HashSet::from([MouseButton::Left]),
+        };
+        game.handle_events();
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, row, col), Some(Sprite::Empty));
+        // moving off the pressed cell raises it and releasing there does nothing
+        game.input.mouse.x += CELL as i32;
+        game.handle_events();
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, row, col), Some(Sprite::Unknown));
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+        assert!(!game.model.get_cell(row, col).is_visible());
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+
+use crate::camera::Camera;
+use crate::config::Configuration;
+use crate::game_model::{replay, GameModel, Grid};
+use crate::minesweeper::{Minesweeper, Size};
+use crate::render::{NoInput, SdlRenderer};
+use crate::resources::{self, FontManager, TextureManager};
+
+// Usage: render <replay or layout file> <output png> [cell size]
+// Draws the saved replay or the game described by text layout without opening a window
+pub fn run_render_cli(args: &[String]) -> Result<(), String> {
+    let (Some(input), Some(output)) = (args.first(), args.get(1)) else {
+        return Err("Usage: render <replay or layout file> <output png> [cell size]".to_string());
+    };
+    let cell = match args.get(2) {
+        Some(value) => value.parse::<u32>().map_err(|e| e.to_string())?,
+        None => 64,
+    };
+    let text = fs::read_to_string(input).map_err(|e| format!("{input}: {e}"))?;
+    // layouts are replays without moves
+    let model = replay::parse(&text)?;
+    let window_size = image_size(&model, cell);
+    let surface = Surface::new(
+        window_size.width,
+        window_size.height,
+        PixelFormatEnum::RGBA32,
+    )?;
+    let canvas = surface.into_canvas()?;
+    let texture_creator = canvas.texture_creator();
+    let texture_manager = TextureManager::new(&texture_creator);
+    let ttf_context = sdl2::ttf::init().map_err(|err| err.to_string())?;
+    let font_manager = FontManager::new(&ttf_context);
+    let text_texture_creator = canvas.texture_creator();
+    let config = Configuration::load(Path::new(crate::CONFIG_FILE))?;
+    let theme = resources::find_theme(config.theme());
+    let color_manager = theme.colors().clone();
+    let renderer = SdlRenderer::new(
+        canvas,
+        text_texture_creator,
+        texture_manager,
+        font_manager,
+        theme,
+    );
+
+    let mut minesweeper = Minesweeper::new(
+        model,
+        renderer,
+        NoInput,
+        color_manager,
+        Camera::new(cell, cell),
+        window_size,
+    );
+    // the image shows the board only
+    minesweeper.set_result_shown(false);
+    minesweeper.request_screenshot(PathBuf::from(output));
+    minesweeper.draw()
+}
+
+fn image_size(model: &GameModel, cell: u32) -> Size {
+    let (rows, cols) = model.board_size();
+    // shifted rows of hex boards stick out by half a cell
+    let shift = (model.grid() == Grid::Hex && rows > 1) as u32 * cell / 2;
+    Size {
+        height: cell * rows as u32,
+        width: cell * cols as u32 + shift,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{RecordingRenderer, Sprite};
+    use crate::resources::ColorManager;
+
+    #[test]
+    fn export_draws_saved_game() {
+        let mut model = replay::parse("..*\n...\n..*").unwrap();
+        model.open_cell(1, 0);
+        model.flag_cell(0, 2);
+        let model = replay::parse(&replay::to_text(&model)).unwrap();
+        let window_size = image_size(&model, 10);
+        assert_eq!((window_size.width, window_size.height), (30, 30));
+        let mut minesweeper = Minesweeper::new(
+            model,
+            RecordingRenderer::default(),
+            NoInput,
+            ColorManager::default(),
+            Camera::new(10, 10),
+            window_size,
+        );
+        minesweeper.draw().unwrap();
+        let renderer = minesweeper.renderer();
+        assert_eq!(renderer.sprite_at(5, 5), Some(Sprite::Empty));
+        assert_eq!(renderer.sprite_at(15, 15), Some(Sprite::Number(2)));
+        assert_eq!(renderer.sprite_at(25, 5), Some(Sprite::Flag));
+    }
+
+    #[test]
+    fn export_leaves_result_out() {
+        let model = replay::parse("X.\n..").unwrap();
+        let window_size = image_size(&model, 10);
+        let mut minesweeper = Minesweeper::new(
+            model,
+            RecordingRenderer::default(),
+            NoInput,
+            ColorManager::default(),
+            Camera::new(10, 10),
+            window_size,
+        );
+        minesweeper.set_result_shown(false);
+        minesweeper.draw().unwrap();
+        let renderer = minesweeper.renderer();
+        assert_eq!(renderer.sprite_at(5, 5), Some(Sprite::BombExploded));
+        assert!(renderer.texts().is_empty());
+    }
+}
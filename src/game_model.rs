@@ -1,6 +1,19 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::time::Instant;
 
+mod board;
+pub use board::*;
+mod field;
+pub use field::*;
+mod mask;
+pub use mask::*;
+
+pub mod endless;
+pub mod layered;
+pub mod layout;
+pub mod replay;
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum State {
     InProgress,
@@ -8,245 +21,333 @@ pub enum State {
     Lose,
 }
 
-#[derive(Clone, Copy)]
-pub struct Cell {
-    is_visible: bool,
-    is_safe: bool,
-    is_flagged: bool,
-    mines_count: usize,
+// What changed after an operation on the model, lets other parts react without polling the board
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GameEvent {
+    // Opened cells, starting from the clicked one and then by distance from it
+    CellsRevealed(Vec<(usize, usize)>),
+    Flagged(usize, usize),
+    Unflagged(usize, usize),
+    Exploded(usize, usize),
+    Won,
+    Restarted,
 }
 
-impl Cell {
-    fn new() -> Self {
-        Self {
-            is_visible: false,
-            is_safe: true,
-            is_flagged: false,
-            mines_count: 0,
-        }
-    }
-
-    pub fn is_visible(&self) -> bool {
-        self.is_visible
-    }
-
-    pub fn is_safe(&self) -> bool {
-        self.is_safe
-    }
-
-    pub fn is_flagged(&self) -> bool {
-        self.is_flagged
-    }
-
-    pub fn mines_count(&self) -> usize {
-        self.mines_count
-    }
-}
-
-enum Op {
-    Inc,
-    Dec,
-    None,
+// Player action recorded in the move log
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Move {
+    Open(usize, usize),
+    Flag(usize, usize),
+    Chord(usize, usize),
 }
 
-impl Op {
-    fn is_applicable(&self, value: usize, max_value: usize) -> bool {
-        match self {
-            Op::Inc => value + 1 < max_value,
-            Op::Dec => value > 0,
-            _ => true,
-        }
+// Checks the rule every start follows: at least one of the `cells` cells is left without
+// mines and the rows of toroidal hex boards pair up
+pub fn check_board(
+    cells: usize,
+    rows: usize,
+    mines: usize,
+    max_cell_mines: usize,
+    grid: Grid,
+    topology: Topology,
+) -> Result<(), String> {
+    let slots = cells * max_cell_mines;
+    if mines + max_cell_mines > slots {
+        let limit = slots.saturating_sub(max_cell_mines);
+        return Err(format!("Too many mines for the board, at most {limit} fit"));
     }
-
-    fn apply(&self, value: usize) -> usize {
-        match self {
-            Op::Inc => value + 1,
-            Op::Dec => value - 1,
-            _ => value,
-        }
+    if !grid.fits(rows, topology) {
+        return Err("Toroidal hex board needs an even number of rows".to_string());
     }
+    Ok(())
 }
 
-pub struct BoardCoordinate {
-    row: usize,
-    col: usize,
+pub struct GameModel {
+    board: Board,
+    state: State,
+    mines: usize,
+    start_time: Instant,
+    last_step: Option<(usize, usize)>,
+    rng: StdRng,
+    moves: Vec<Move>,
+    is_first_click_safe: bool,
+    grid: Grid,
+    topology: Topology,
+    // most mines a single cell may hold
+    max_cell_mines: usize,
+    // shape of the board, all cells are in the game without it
+    mask: Option<Mask>,
+    // cells opened before the first move, as puzzles give them
+    given_cells: Vec<(usize, usize)>,
+    // mines are placed by the first opened cell
+    is_mines_pending: bool,
 }
 
-impl BoardCoordinate {
-    fn fake() -> Self {
-        Self {
-            row: usize::MAX,
-            col: usize::MAX,
+impl Move {
+    // Cell the move was made on
+    pub fn cell(&self) -> (usize, usize) {
+        match *self {
+            Move::Open(row, col) | Move::Flag(row, col) | Move::Chord(row, col) => (row, col),
         }
     }
 }
 
-pub struct GameModel {
-    board: Vec<Vec<Cell>>,
-    state: State,
-    mines: usize,
-    row_count: usize,
-    col_count: usize,
-    start_time: Instant,
-    last_step: BoardCoordinate,
+impl Default for GameModel {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameModel {
     pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
         Self {
-            board: vec![],
+            board: Board::default(),
             state: State::InProgress,
             mines: 0,
-            row_count: 0,
-            col_count: 0,
             start_time: Instant::now(),
-            last_step: BoardCoordinate::fake(),
+            last_step: None,
+            rng,
+            moves: Vec::new(),
+            is_first_click_safe: false,
+            grid: Grid::Square,
+            topology: Topology::Bounded,
+            max_cell_mines: 1,
+            mask: None,
+            given_cells: Vec::new(),
+            is_mines_pending: false,
         }
     }
 
-    pub fn start(&mut self, rows: usize, cols: usize, mines: usize) {
-        if mines >= rows * cols {
-            return;
-        }
-        self.row_count = rows;
-        self.col_count = cols;
+    // The first opened cell and its neighbours never have mines, applies from the next start
+    pub fn set_first_click_safe(&mut self, is_safe: bool) {
+        self.is_first_click_safe = is_safe;
+    }
+
+    // Shape of the cells, applies from the next start
+    pub fn set_grid(&mut self, grid: Grid) {
+        self.grid = grid;
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.board.grid()
+    }
+
+    // Edges of the board, applies from the next start
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.board.topology()
+    }
+
+    // Cells hold up to `count` mines and flags cycle through the counts, applies from the
+    // next start
+    pub fn set_max_cell_mines(&mut self, count: usize) {
+        self.max_cell_mines = count.clamp(1, MAX_CELL_MINES);
+    }
+
+    pub fn max_cell_mines(&self) -> usize {
+        self.max_cell_mines
+    }
+
+    // Cuts the holes of the mask out of boards of its size, applies from the next start
+    pub fn set_mask(&mut self, mask: Option<Mask>) {
+        self.mask = mask;
+    }
+
+    pub fn mask(&self) -> Option<&Mask> {
+        self.mask.as_ref()
+    }
+
+    pub fn start(&mut self, rows: usize, cols: usize, mines: usize) -> Result<(), String> {
+        let cells = match &self.mask {
+            Some(mask) if mask.size() != (rows, cols) => {
+                let (mask_rows, mask_cols) = mask.size();
+                return Err(format!(
+                    "Mask of {mask_rows}x{mask_cols} cells doesn't fit the {rows}x{cols} board"
+                ));
+            }
+            Some(mask) => mask.cells_count(),
+            None => rows * cols,
+        };
+        let limit = self.max_cell_mines;
+        check_board(cells, rows, mines, limit, self.grid, self.topology)?;
         self.mines = mines;
         self.start_time = Instant::now();
         self.state = State::InProgress;
-        self.board = vec![vec![Cell::new(); self.col_count]; self.row_count];
-        self.last_step = BoardCoordinate::fake();
-        self.fill_mines();
-        self.fill_safe_numbers();
-    }
-
-    pub fn restart(&mut self) {
-        self.start(self.row_count, self.col_count, self.mines);
-    }
-
-    fn fill_mines(&mut self) {
-        let mut rng = rand::thread_rng();
-        let mut counter = self.mines;
-        while counter > 0 {
-            let row = rng.gen_range(0..self.row_count);
-            let col = rng.gen_range(0..self.col_count);
-            let mut cell = &mut self.board[row][col];
-            if cell.is_safe {
-                cell.is_safe = false;
-                counter -= 1;
-            }
+        self.board = Board::with_shape(rows, cols, self.grid, self.topology);
+        if let Some(mask) = &self.mask {
+            self.board.set_mask(mask);
         }
+        self.last_step = None;
+        self.moves.clear();
+        self.given_cells.clear();
+        self.is_mines_pending = self.is_first_click_safe;
+        if !self.is_mines_pending {
+            let (shape, cells) = self.board.split_mut();
+            fill_mines(shape, cells, &mut self.rng, mines, self.max_cell_mines, &[]);
+            self.fill_safe_numbers();
+        }
+        Ok(())
+    }
+
+    pub fn restart(&mut self) -> Vec<GameEvent> {
+        // nothing to restart before the first start
+        if self
+            .start(self.board.rows(), self.board.cols(), self.mines)
+            .is_err()
+        {
+            return Vec::new();
+        }
+        vec![GameEvent::Restarted]
     }
 
     fn fill_safe_numbers(&mut self) {
-        for row in 0..self.row_count {
-            for col in 0..self.col_count {
-                self.board[row][col].mines_count = self.calc_mines_count(row, col);
+        let (shape, cells) = self.board.split_mut();
+        fill_safe_numbers(shape, cells);
+    }
+
+    pub fn open_cell(&mut self, row: usize, col: usize) -> Vec<GameEvent> {
+        if !self.can_touch_cell(row, col) {
+            return Vec::new();
+        }
+        self.last_step = Some((row, col));
+        self.moves.push(Move::Open(row, col));
+        if self.is_mines_pending {
+            let (shape, cells) = self.board.split_mut();
+            let (mines, slots) = (self.mines, self.max_cell_mines);
+            place_mines_around(shape, cells, &mut self.rng, mines, slots, (row, col));
+            self.is_mines_pending = false;
+        }
+        self.open_cells(&[(row, col)])
+    }
+
+    // Opens covered neighbours of the opened number if it has as many flags around
+    pub fn chord(&mut self, row: usize, col: usize) -> Vec<GameEvent> {
+        if self.state != State::InProgress || row >= self.board.rows() || col >= self.board.cols() {
+            return Vec::new();
+        }
+        let covered = chord_cells(self.board.shape(), self.board.cells(), (row, col));
+        if covered.is_empty() {
+            return Vec::new();
+        }
+        self.last_step = Some((row, col));
+        self.moves.push(Move::Chord(row, col));
+        self.open_cells(&covered)
+    }
+
+    // Opens the cells flood filling empty areas, the first mine among them loses the game
+    fn open_cells(&mut self, cells: &[(usize, usize)]) -> Vec<GameEvent> {
+        let (shape, board_cells) = self.board.split_mut();
+        let (revealed, exploded) = open_cells(shape, board_cells, cells);
+        let mut events = Vec::new();
+        if !revealed.is_empty() {
+            events.push(GameEvent::CellsRevealed(revealed));
+        }
+        match exploded {
+            Some((row, col)) => {
+                self.last_step = Some((row, col));
+                self.state = State::Lose;
+                events.push(GameEvent::Exploded(row, col));
             }
+            None => events.extend(self.track_win_state()),
         }
+        events
     }
 
-    fn calc_mines_count(&mut self, row: usize, col: usize) -> usize {
-        self.adjacent_cells(row, col)
-            .iter()
-            .filter(|(r, c)| !self.board[*r][*c].is_safe)
-            .count()
-    }
-
-    fn adjacent_cells(&mut self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        vec![
-            (Op::Inc, Op::None),
-            (Op::Dec, Op::None),
-            (Op::None, Op::Inc),
-            (Op::None, Op::Dec),
-            (Op::Inc, Op::Inc),
-            (Op::Dec, Op::Dec),
-            (Op::Inc, Op::Dec),
-            (Op::Dec, Op::Inc),
-        ]
-        .iter()
-        .filter(|(r, c)| {
-            r.is_applicable(row, self.row_count) && c.is_applicable(col, self.col_count)
-        })
-        .map(|(r, c)| (r.apply(row), c.apply(col)))
-        .collect()
-    }
-
-    pub fn open_cell(&mut self, row: usize, col: usize) {
+    pub fn flag_cell(&mut self, row: usize, col: usize) -> Vec<GameEvent> {
         if !self.can_touch_cell(row, col) {
-            return;
-        }
-        self.last_step = BoardCoordinate { row, col };
-        let current = &mut self.board[row][col];
-        current.is_flagged = false;
-        if current.is_safe {
-            if current.mines_count > 0 {
-                current.is_visible = true;
-            } else {
-                let mut adjacent = vec![(row, col)];
-                while let Some((row, col)) = adjacent.pop() {
-                    {
-                        let mut cell = &mut self.board[row][col];
-                        if cell.is_visible {
-                            continue;
-                        }
-                        cell.is_visible = true;
-                    }
-                    for (r, c) in self.adjacent_cells(row, col) {
-                        let cell = &mut self.board[r][c];
-                        if !cell.is_safe {
-                            continue;
-                        }
-                        if cell.mines_count == 0 {
-                            adjacent.push((r, c));
-                        } else {
-                            cell.is_visible = true;
-                        }
-                    }
-                }
-            }
-            self.track_win_state();
+            return Vec::new();
+        }
+        self.moves.push(Move::Flag(row, col));
+        // flags cycle through the mine counts a cell may hold and back to none
+        let limit = self.max_cell_mines;
+        let cell = &mut self.board[(row, col)];
+        cell.set_flags((cell.flags() + 1) % (limit + 1));
+        let mut events = if cell.is_flagged() {
+            vec![GameEvent::Flagged(row, col)]
         } else {
-            self.state = State::Lose;
+            vec![GameEvent::Unflagged(row, col)]
+        };
+        events.extend(self.track_win_state());
+        events
+    }
+
+    pub fn apply(&mut self, action: Move) -> Vec<GameEvent> {
+        match action {
+            Move::Open(row, col) => self.open_cell(row, col),
+            Move::Flag(row, col) => self.flag_cell(row, col),
+            Move::Chord(row, col) => self.chord(row, col),
         }
     }
 
-    pub fn flag_cell(&mut self, row: usize, col: usize) {
-        if !self.can_touch_cell(row, col) {
-            return;
+    // Moves changed the board since the start, in order
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    // Cells opened before the first move
+    pub fn given_cells(&self) -> &[(usize, usize)] {
+        &self.given_cells
+    }
+
+    // The same board as it was at the start after replaying the first `count` moves
+    pub fn position_after(&self, count: usize) -> GameModel {
+        let mut board = self.board.clone();
+        for cell in board.cells_mut() {
+            cell.set_visible(false);
+            cell.set_flags(0);
+        }
+        for &cell in &self.given_cells {
+            board[cell].set_visible(true);
+        }
+        let mut model = GameModel {
+            board,
+            state: State::InProgress,
+            mines: self.mines,
+            start_time: Instant::now(),
+            last_step: None,
+            rng: self.rng.clone(),
+            moves: Vec::new(),
+            is_first_click_safe: false,
+            grid: self.grid,
+            topology: self.topology,
+            max_cell_mines: self.max_cell_mines,
+            mask: self.mask.clone(),
+            given_cells: self.given_cells.clone(),
+            is_mines_pending: false,
+        };
+        for action in &self.moves[..count.min(self.moves.len())] {
+            model.apply(*action);
         }
-        self.board[row][col].is_flagged = !self.board[row][col].is_flagged;
-        self.track_win_state();
+        model
     }
 
     fn can_touch_cell(&self, row: usize, col: usize) -> bool {
         if self.state != State::InProgress {
             return false;
         }
-        if row >= self.row_count && col >= self.col_count {
+        if row >= self.board.rows() || col >= self.board.cols() {
             return false;
         }
-        !self.board[row][col].is_visible
+        !self.board[(row, col)].is_visible() && !self.board.is_hole((row, col))
     }
 
-    fn track_win_state(&mut self) {
-        let mut is_flag_win = true;
-        let mut is_open_win = true;
-        for r in 0..self.row_count {
-            for c in 0..self.col_count {
-                let cell = &self.board[r][c];
-                if cell.is_safe {
-                    is_open_win &= cell.is_visible && !cell.is_flagged;
-                } else {
-                    is_flag_win &= cell.is_flagged;
-                }
-                if !is_flag_win && !is_open_win {
-                    return;
-                }
-            }
-        }
-        if is_flag_win || is_open_win {
-            self.state = State::Win;
+    fn track_win_state(&mut self) -> Option<GameEvent> {
+        if self.is_mines_pending || !is_won(self.board.shape(), self.board.cells()) {
+            return None;
         }
+        self.state = State::Win;
+        Some(GameEvent::Won)
     }
 
     pub fn state(&self) -> State {
@@ -254,16 +355,115 @@ impl GameModel {
     }
 
     pub fn board_size(&self) -> (usize, usize) {
-        (self.row_count, self.col_count)
+        (self.board.rows(), self.board.cols())
     }
 
     pub fn get_cell(&self, row: usize, col: usize) -> Cell {
-        self.board[row][col]
+        self.board[(row, col)]
+    }
+
+    // Cell cut out of the board by the mask
+    pub fn is_hole(&self, row: usize, col: usize) -> bool {
+        self.board.is_hole((row, col))
     }
 
     pub fn is_last_step(&self, row: usize, col: usize) -> bool {
-        let last = &self.last_step;
-        last.row == row && last.col == col
+        self.last_step == Some((row, col))
+    }
+
+    pub fn mines_count(&self) -> usize {
+        self.mines
+    }
+
+    pub fn neighbours(&self, row: usize, col: usize) -> Neighbours<'_> {
+        self.board.neighbours(row, col)
+    }
+
+    // Board difficulty value: minimal number of left clicks required to open all safe cells
+    pub fn three_bv(&self) -> usize {
+        let cells = self.board.cells();
+        let mut marked = vec![false; cells.len()];
+        let mut count = 0;
+        for (index, cell) in cells.iter().enumerate() {
+            let is_hole = self.board.is_hole(self.board.coordinate(index));
+            if marked[index] || is_hole || !cell.is_safe() || cell.mines_count() > 0 {
+                continue;
+            }
+            count += 1;
+            let mut opening = vec![self.board.coordinate(index)];
+            marked[index] = true;
+            while let Some((r, c)) = opening.pop() {
+                for (ar, ac) in self.board.neighbours(r, c) {
+                    let index = ar * self.board.cols() + ac;
+                    if marked[index] {
+                        continue;
+                    }
+                    marked[index] = true;
+                    if cells[index].mines_count() == 0 {
+                        opening.push((ar, ac));
+                    }
+                }
+            }
+        }
+        let unmarked = cells
+            .iter()
+            .zip(&marked)
+            .enumerate()
+            .filter(|(index, (cell, is_marked))| {
+                cell.is_safe() && !**is_marked && !self.board.is_hole(self.board.coordinate(*index))
+            })
+            .count();
+        count + unmarked
+    }
+
+    pub fn player_view(&self) -> PlayerView<'_> {
+        PlayerView { model: self }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CellView {
+    // not part of the board
+    Hole,
+    Hidden,
+    // number of flags, more than one only if cells may hold several mines
+    Flagged(usize),
+    Revealed(usize),
+}
+
+// Board as seen by a player: mine locations are not exposed
+pub struct PlayerView<'a> {
+    model: &'a GameModel,
+}
+
+impl<'a> PlayerView<'a> {
+    pub fn board_size(&self) -> (usize, usize) {
+        self.model.board_size()
+    }
+
+    pub fn mines_count(&self) -> usize {
+        self.model.mines_count()
+    }
+
+    pub fn max_cell_mines(&self) -> usize {
+        self.model.max_cell_mines()
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> CellView {
+        let cell = &self.model.board[(row, col)];
+        if self.model.is_hole(row, col) {
+            CellView::Hole
+        } else if cell.is_visible() {
+            CellView::Revealed(cell.mines_count())
+        } else if cell.is_flagged() {
+            CellView::Flagged(cell.flags())
+        } else {
+            CellView::Hidden
+        }
+    }
+
+    pub fn neighbours(&self, row: usize, col: usize) -> Neighbours<'a> {
+        self.model.neighbours(row, col)
     }
 }
 
@@ -274,7 +474,7 @@ mod tests {
     fn create_game() -> GameModel {
         let mut game = GameModel::new();
         let mines = 99;
-        game.start(16, 32, mines);
+        game.start(16, 32, mines).unwrap();
         game
     }
 
@@ -283,18 +483,27 @@ mod tests {
         let game = create_game();
         let found = game
             .board
+            .cells()
             .iter()
-            .map(|row| row.iter().filter(|cell| !cell.is_safe).count())
-            .sum::<usize>();
+            .filter(|cell| !cell.is_safe())
+            .count();
         assert_eq!(found, game.mines);
+        for (index, cell) in game.board.cells().iter().enumerate() {
+            let (row, col) = game.board.coordinate(index);
+            let around = game
+                .neighbours(row, col)
+                .filter(|&neighbour| !game.board[neighbour].is_safe())
+                .count();
+            assert_eq!(cell.mines_count(), around);
+        }
     }
 
     #[test]
     fn model_lose() {
         let mut game = create_game();
-        'outer: for r in 0..game.row_count {
-            for c in 0..game.col_count {
-                if !game.board[r][c].is_safe {
+        'outer: for r in 0..game.board.rows() {
+            for c in 0..game.board.cols() {
+                if !game.board[(r, c)].is_safe() {
                     game.open_cell(r, c);
                     break 'outer;
                 }
@@ -306,9 +515,9 @@ mod tests {
     #[test]
     fn model_win_by_open() {
         let mut game = create_game();
-        for r in 0..game.row_count {
-            for c in 0..game.col_count {
-                if game.board[r][c].is_safe {
+        for r in 0..game.board.rows() {
+            for c in 0..game.board.cols() {
+                if game.board[(r, c)].is_safe() {
                     game.open_cell(r, c);
                 }
             }
@@ -319,9 +528,9 @@ mod tests {
     #[test]
     fn model_win_by_flag() {
         let mut game = create_game();
-        for r in 0..game.row_count {
-            for c in 0..game.col_count {
-                if !game.board[r][c].is_safe {
+        for r in 0..game.board.rows() {
+            for c in 0..game.board.cols() {
+                if !game.board[(r, c)].is_safe() {
                     game.flag_cell(r, c);
                 }
             }
@@ -329,16 +538,323 @@ mod tests {
         assert_eq!(game.state, State::Win);
     }
 
+    #[test]
+    fn model_same_seed_same_layout() {
+        let mut first = GameModel::with_seed(42);
+        let mut second = GameModel::with_seed(42);
+        first.start(16, 30, 99).unwrap();
+        second.start(16, 30, 99).unwrap();
+        for r in 0..first.board.rows() {
+            for c in 0..first.board.cols() {
+                assert_eq!(
+                    first.board[(r, c)].is_safe(),
+                    second.board[(r, c)].is_safe()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn model_three_bv() {
+        let mut game = GameModel::new();
+        game.start(3, 3, 0).unwrap();
+        assert_eq!(game.three_bv(), 1);
+
+        // single mine in the corner: one opening plus nothing else
+        game.board = Board::new(3, 3);
+        game.board[(0, 0)].set_mines(1);
+        game.mines = 1;
+        game.fill_safe_numbers();
+        assert_eq!(game.three_bv(), 1);
+
+        // mine in the center: no openings, every safe cell needs a click
+        game.board = Board::new(3, 3);
+        game.board[(1, 1)].set_mines(1);
+        game.fill_safe_numbers();
+        assert_eq!(game.three_bv(), 8);
+    }
+
     #[test]
     fn model_open_cell_empty_board() {
         let mut game = GameModel::new();
-        game.start(16, 32, 0);
+        game.start(16, 32, 0).unwrap();
         game.open_cell(0, 0);
-        for r in 0..game.row_count {
-            for c in 0..game.col_count {
-                assert!(game.board[r][c].is_visible);
+        for r in 0..game.board.rows() {
+            for c in 0..game.board.cols() {
+                assert!(game.board[(r, c)].is_visible());
             }
         }
         assert_eq!(game.state, State::Win);
     }
+
+    #[test]
+    fn model_events() {
+        let mut game = GameModel::new();
+        game.start(1, 6, 1).unwrap();
+        game.board = Board::new(1, 6);
+        game.board[(0, 4)].set_mines(1);
+        game.fill_safe_numbers();
+
+        assert_eq!(game.flag_cell(0, 0), vec![GameEvent::Flagged(0, 0)]);
+        assert_eq!(game.flag_cell(0, 0), vec![GameEvent::Unflagged(0, 0)]);
+        assert_eq!(
+            game.open_cell(0, 1),
+            vec![GameEvent::CellsRevealed(vec![
+                (0, 1),
+                (0, 2),
+                (0, 0),
+                (0, 3)
+            ])]
+        );
+        assert!(game.open_cell(0, 1).is_empty());
+        assert_eq!(
+            game.flag_cell(0, 4),
+            vec![GameEvent::Flagged(0, 4), GameEvent::Won]
+        );
+        assert!(game.open_cell(0, 4).is_empty());
+
+        assert_eq!(game.restart(), vec![GameEvent::Restarted]);
+        let mine = (0..6).find(|c| !game.board[(0, *c)].is_safe()).unwrap();
+        assert_eq!(game.open_cell(0, mine), vec![GameEvent::Exploded(0, mine)]);
+        assert_eq!(game.state(), State::Lose);
+    }
+
+    #[test]
+    fn model_chord() {
+        let mut game = GameModel::new();
+        game.start(3, 3, 1).unwrap();
+        game.board = Board::new(3, 3);
+        game.board[(0, 0)].set_mines(1);
+        game.fill_safe_numbers();
+
+        game.open_cell(1, 1);
+        assert!(game.chord(1, 1).is_empty());
+        game.flag_cell(0, 1);
+        // wrong flag: chord opens the mine
+        assert_eq!(
+            game.chord(1, 1),
+            vec![
+                GameEvent::CellsRevealed(vec![
+                    (2, 1),
+                    (2, 2),
+                    (2, 0),
+                    (1, 0),
+                    (1, 2),
+                    (0, 2),
+                    (0, 1)
+                ]),
+                GameEvent::Exploded(0, 0)
+            ]
+        );
+        assert_eq!(game.state(), State::Lose);
+        assert!(game.is_last_step(0, 0));
+
+        game.start(3, 4, 2).unwrap();
+        game.board = Board::new(3, 4);
+        game.board[(0, 0)].set_mines(1);
+        game.board[(0, 3)].set_mines(1);
+        game.fill_safe_numbers();
+        game.open_cell(1, 1);
+        game.flag_cell(0, 0);
+        let events = game.chord(1, 1);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1], GameEvent::Won);
+        assert_eq!(game.moves().last(), Some(&Move::Chord(1, 1)));
+    }
+
+    #[test]
+    fn model_first_click_safe() {
+        let mut game = GameModel::with_seed(3);
+        game.set_first_click_safe(true);
+        game.start(5, 5, 10).unwrap();
+        assert!(game.board.cells().iter().all(Cell::is_safe));
+        // flags before the mines are placed don't win
+        assert_eq!(game.flag_cell(4, 4), vec![GameEvent::Flagged(4, 4)]);
+        let events = game.open_cell(2, 2);
+        assert!(matches!(events[0], GameEvent::CellsRevealed(_)));
+        assert_eq!(game.get_cell(2, 2).mines_count(), 0);
+        let mines = game.board.cells().iter().filter(|cell| !cell.is_safe());
+        assert_eq!(mines.count(), 10);
+
+        // no room for the neighbours, only the clicked cell is kept free
+        game.start(99, 99, 9800).unwrap();
+        assert_eq!(game.open_cell(50, 50).last(), Some(&GameEvent::Won));
+        let mines = game.board.cells().iter().filter(|cell| !cell.is_safe());
+        assert_eq!(mines.count(), 9800);
+    }
+
+    #[test]
+    fn model_hex_board() {
+        // diagonal neighbours of square boards don't touch on hex ones
+        let mut game = layout::parse("grid hex\n*....\n.....\n.....\n.....").unwrap();
+        assert_eq!(game.get_cell(1, 0).mines_count(), 1);
+        assert_eq!(game.get_cell(0, 1).mines_count(), 1);
+        assert_eq!(game.get_cell(1, 1).mines_count(), 0);
+        assert_eq!(game.open_cell(3, 4).last(), Some(&GameEvent::Won));
+
+        // a column of mines splits the board in two
+        let mut game = layout::parse("grid hex\n..*..\n..*..\n..*..\n..*..").unwrap();
+        let events = game.open_cell(0, 0);
+        assert!(matches!(&events[..], [GameEvent::CellsRevealed(cells)] if cells.len() == 8));
+        assert!((0..4).all(|row| !game.get_cell(row, 3).is_visible()));
+        assert_eq!(game.state(), State::InProgress);
+        let events = game.open_cell(3, 4);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events.last(), Some(&GameEvent::Won));
+
+        let mut game = GameModel::with_seed(1);
+        game.set_grid(Grid::Hex);
+        game.start(6, 6, 5).unwrap();
+        assert_eq!(game.grid(), Grid::Hex);
+        assert_eq!(game.neighbours(2, 2).count(), 6);
+    }
+
+    #[test]
+    fn model_large_neighbourhoods() {
+        // numbers above eight
+        let mut game = layout::parse("grid radius2\n*****\n*****\n**.**\n*****\n*****").unwrap();
+        assert_eq!(game.get_cell(2, 2).mines_count(), 24);
+        assert_eq!(game.open_cell(2, 2).last(), Some(&GameEvent::Won));
+
+        // knight's moves never reach the middle of 3x3 board and go around it
+        let mut game = layout::parse("grid knight\n...\n.*.\n...").unwrap();
+        assert_eq!(game.get_cell(0, 1).mines_count(), 0);
+        let events = game.open_cell(0, 0);
+        assert!(matches!(&events[0], GameEvent::CellsRevealed(cells) if cells.len() == 8));
+        assert_eq!(events.last(), Some(&GameEvent::Won));
+
+        // cross neighbours don't see the diagonal mine
+        let game = layout::parse("grid cross\n*.\n..").unwrap();
+        assert_eq!(game.get_cell(1, 1).mines_count(), 0);
+        assert_eq!(game.get_cell(0, 1).mines_count(), 1);
+    }
+
+    #[test]
+    fn model_toroidal_board() {
+        let mut game = GameModel::with_seed(4);
+        game.set_topology(Topology::Toroidal);
+        game.start(4, 5, 1).unwrap();
+        assert_eq!(game.topology(), Topology::Toroidal);
+        game.board = Board::with_shape(4, 5, Grid::Square, Topology::Toroidal);
+        game.board[(0, 0)].set_mines(1);
+        game.fill_safe_numbers();
+        // the mine is counted across the edges
+        for (row, col) in [(3, 4), (0, 4), (3, 0), (1, 4)] {
+            assert_eq!(game.get_cell(row, col).mines_count(), 1);
+        }
+        assert_eq!(game.get_cell(2, 2).mines_count(), 0);
+        assert_eq!(game.open_cell(2, 2).last(), Some(&GameEvent::Won));
+        game.restart();
+        assert_eq!(game.topology(), Topology::Toroidal);
+        assert_eq!(game.neighbours(0, 0).count(), 8);
+    }
+
+    #[test]
+    fn model_multi_mine_cells() {
+        let mut game = GameModel::with_seed(6);
+        game.set_max_cell_mines(3);
+        assert_eq!(
+            game.start(4, 4, 46),
+            Err("Too many mines for the board, at most 45 fit".to_string())
+        );
+        assert_eq!(game.board_size(), (0, 0));
+        game.start(4, 4, 30).unwrap();
+        let cells = game.board.cells();
+        assert_eq!(cells.iter().map(Cell::mines).sum::<usize>(), 30);
+        assert!(cells.iter().all(|cell| cell.mines() <= 3));
+        assert!(cells.iter().any(|cell| cell.mines() > 1));
+        for index in 0..16 {
+            let (row, col) = game.board.coordinate(index);
+            let around = game
+                .neighbours(row, col)
+                .map(|neighbour| game.board[neighbour].mines())
+                .sum::<usize>();
+            assert_eq!(game.get_cell(row, col).mines_count(), around);
+        }
+
+        // flags cycle through the counts, the win needs every count right
+        let mut game = layout::parse("cell_mines 2\n2.\n..").unwrap();
+        assert_eq!(game.flag_cell(0, 0), vec![GameEvent::Flagged(0, 0)]);
+        assert_eq!(game.get_cell(0, 0).flags(), 1);
+        assert_eq!(game.state(), State::InProgress);
+        assert_eq!(
+            game.flag_cell(0, 0),
+            vec![GameEvent::Flagged(0, 0), GameEvent::Won]
+        );
+        let mut game = layout::parse("cell_mines 2\n2*\n..").unwrap();
+        game.flag_cell(0, 0);
+        game.flag_cell(0, 0);
+        assert_eq!(game.flag_cell(0, 0), vec![GameEvent::Unflagged(0, 0)]);
+
+        // chording counts every flag of a cell
+        let mut game = layout::parse("cell_mines 2\n2...*\n.....").unwrap();
+        game.open_cell(0, 1);
+        game.flag_cell(0, 0);
+        assert!(game.chord(0, 1).is_empty());
+        game.flag_cell(0, 0);
+        assert!(!game.chord(0, 1).is_empty());
+        assert!(game.get_cell(1, 3).is_visible());
+        assert_eq!(game.state(), State::InProgress);
+    }
+
+    #[test]
+    fn model_mask_holes() {
+        let diamond = Mask::new(5, 5, |row, col| row.abs_diff(2) + col.abs_diff(2) <= 2);
+        let mut game = GameModel::with_seed(7);
+        game.set_mask(Some(diamond.clone()));
+        // the mask fits only boards of its size, mines fit only its cells
+        assert!(game.start(6, 5, 3).is_err());
+        assert!(game.start(5, 5, 13).is_err());
+        assert_eq!(game.board_size(), (0, 0));
+
+        game.set_first_click_safe(true);
+        game.start(5, 5, 4).unwrap();
+        assert!(game.open_cell(0, 0).is_empty());
+        assert!(game.flag_cell(4, 4).is_empty());
+        game.open_cell(2, 2);
+        let cells = (0..25).map(|index| game.board.coordinate(index));
+        let mut safe = Vec::new();
+        for (row, col) in cells {
+            let cell = game.get_cell(row, col);
+            if game.is_hole(row, col) {
+                assert!(cell.is_safe() && !cell.is_visible() && cell.mines_count() == 0);
+                assert_eq!(game.player_view().cell(row, col), CellView::Hole);
+            } else if cell.is_safe() {
+                safe.push((row, col));
+            }
+        }
+        assert_eq!(safe.len(), diamond.cells_count() - 4);
+        assert!(game.three_bv() <= safe.len());
+        // holes stay covered and the game is still won
+        for (row, col) in safe {
+            game.open_cell(row, col);
+        }
+        assert_eq!(game.state(), State::Win);
+        assert!(game.position_after(0).is_hole(0, 0));
+    }
+
+    #[test]
+    fn model_mines_uniform() {
+        // every allowed cell of 4x4 board gets a mine in 5 of 12 games on average
+        const GAMES: usize = 6000;
+        let mut counts = [0usize; 16];
+        for seed in 0..GAMES {
+            let mut game = GameModel::with_seed(seed as u64);
+            game.set_first_click_safe(true);
+            game.start(4, 4, 5).unwrap();
+            game.open_cell(0, 0);
+            for (index, cell) in game.board.cells().iter().enumerate() {
+                counts[index] += !cell.is_safe() as usize;
+            }
+        }
+        let excluded = [0, 1, 4, 5];
+        assert!(excluded.iter().all(|&index| counts[index] == 0));
+        let expected = (GAMES * 5) as f64 / 12.0;
+        let chi_squared = (0..16)
+            .filter(|index| !excluded.contains(index))
+            .map(|index| (counts[index] as f64 - expected).powi(2) / expected)
+            .sum::<f64>();
+        // critical value of 11 degrees of freedom at 0.001 significance
+        assert!(chi_squared < 31.26, "{chi_squared} {counts:?}");
+    }
 }
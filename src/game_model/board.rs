@@ -0,0 +1,609 @@
+use super::{Field, Mask};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+// Cell state packed into two bytes: mines around in the low byte, enough for the largest
+// neighbourhood full of cells with the most mines, then mines and flags in the cell
+const COUNT_MASK: u16 = 0xff;
+const MINES_SHIFT: u32 = 8;
+const FLAGS_SHIFT: u32 = 11;
+const CELL_MASK: u16 = 0b111;
+const VISIBLE: u16 = 1 << 14;
+
+// Most mines a single cell may hold
+pub const MAX_CELL_MINES: usize = CELL_MASK as usize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Cell(u16);
+
+impl Cell {
+    pub fn is_visible(&self) -> bool {
+        self.0 & VISIBLE != 0
+    }
+
+    pub fn is_safe(&self) -> bool {
+        self.mines() == 0
+    }
+
+    // Mines in the cell itself, at most one unless the game allows more
+    pub fn mines(&self) -> usize {
+        (self.0 >> MINES_SHIFT & CELL_MASK) as usize
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        self.flags() > 0
+    }
+
+    // Flags put on the cell, one per mine the player expects there
+    pub fn flags(&self) -> usize {
+        (self.0 >> FLAGS_SHIFT & CELL_MASK) as usize
+    }
+
+    // Total of mines in the neighbour cells
+    pub fn mines_count(&self) -> usize {
+        (self.0 & COUNT_MASK) as usize
+    }
+
+    pub(super) fn set_visible(&mut self, value: bool) {
+        if value {
+            self.0 |= VISIBLE;
+        } else {
+            self.0 &= !VISIBLE;
+        }
+    }
+
+    pub(super) fn set_mines(&mut self, count: usize) {
+        self.set_field(MINES_SHIFT, count);
+    }
+
+    pub(super) fn set_flags(&mut self, count: usize) {
+        self.set_field(FLAGS_SHIFT, count);
+    }
+
+    pub(super) fn set_mines_count(&mut self, count: usize) {
+        debug_assert!(count <= COUNT_MASK as usize);
+        self.0 = self.0 & !COUNT_MASK | count as u16;
+    }
+
+    fn set_field(&mut self, shift: u32, count: usize) {
+        debug_assert!(count <= MAX_CELL_MINES);
+        self.0 = self.0 & !(CELL_MASK << shift) | (count as u16) << shift;
+    }
+}
+
+// How the edges of the board connect, decides which cells are neighbours
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Topology {
+    // cells at the edges have fewer neighbours
+    #[default]
+    Bounded,
+    // opposite edges are joined, every cell has eight neighbours
+    Toroidal,
+}
+
+impl Topology {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topology::Bounded => "bounded",
+            Topology::Toroidal => "toroidal",
+        }
+    }
+
+    // Cell shifted by the offset, None if it falls beyond the edge
+    pub fn shift(
+        &self,
+        (row, col): (usize, usize),
+        (dr, dc): (isize, isize),
+        (rows, cols): (usize, usize),
+    ) -> Option<(usize, usize)> {
+        match self {
+            Topology::Bounded => {
+                let row = row.wrapping_add_signed(dr);
+                let col = col.wrapping_add_signed(dc);
+                // wrapped below zero values are too large as well
+                (row < rows && col < cols).then_some((row, col))
+            }
+            Topology::Toroidal => Some((
+                (row as isize + dr).rem_euclid(rows as isize) as usize,
+                (col as isize + dc).rem_euclid(cols as isize) as usize,
+            )),
+        }
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bounded" => Ok(Topology::Bounded),
+            "toroidal" => Ok(Topology::Toroidal),
+            _ => Err(format!("Unknown topology '{s}'")),
+        }
+    }
+}
+
+// Shape of the cells and which cells count as neighbours
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum Grid {
+    // eight neighbours, diagonals included
+    #[default]
+    Square,
+    // six neighbours, odd rows are shifted right by half a cell
+    Hex,
+    // square cells, the eight cells a chess knight's move away
+    Knight,
+    // square cells, the four sharing a side
+    Cross,
+    // square cells, the 24 others in the 5x5 square around
+    Radius2,
+}
+
+impl Grid {
+    pub const ALL: [Grid; 5] = [
+        Grid::Square,
+        Grid::Hex,
+        Grid::Knight,
+        Grid::Cross,
+        Grid::Radius2,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Grid::Square => "square",
+            Grid::Hex => "hex",
+            Grid::Knight => "knight",
+            Grid::Cross => "cross",
+            Grid::Radius2 => "radius2",
+        }
+    }
+
+    // Offsets of the neighbours of cells in the row
+    pub fn offsets(&self, row: usize) -> &'static [(isize, isize)] {
+        match self {
+            Grid::Square => &OFFSETS,
+            Grid::Hex if row.is_multiple_of(2) => &HEX_EVEN_ROW_OFFSETS,
+            Grid::Hex => &HEX_ODD_ROW_OFFSETS,
+            Grid::Knight => &KNIGHT_OFFSETS,
+            Grid::Cross => &OFFSETS[..4],
+            Grid::Radius2 => &RADIUS_2_OFFSETS,
+        }
+    }
+
+    // Largest row or column distance to a neighbour
+    fn reach(&self) -> usize {
+        match self {
+            Grid::Knight | Grid::Radius2 => 2,
+            _ => 1,
+        }
+    }
+
+    // Rows of hex boards with joined edges pair up only if their number is even
+    pub fn fits(&self, rows: usize, topology: Topology) -> bool {
+        *self != Grid::Hex || topology != Topology::Toroidal || rows.is_multiple_of(2)
+    }
+}
+
+impl FromStr for Grid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase();
+        Grid::ALL
+            .into_iter()
+            .find(|grid| grid.name() == name)
+            .ok_or_else(|| format!("Unknown grid '{s}'"))
+    }
+}
+
+// Size, neighbourhood and holes of a board, tells which of its cells touch
+#[derive(Clone, Default)]
+pub struct Shape {
+    rows: usize,
+    cols: usize,
+    grid: Grid,
+    topology: Topology,
+    // cells left out of the game
+    holes: Option<Box<[bool]>>,
+}
+
+impl Shape {
+    pub fn neighbours(&self, row: usize, col: usize) -> Neighbours<'_> {
+        let reach = self.grid.reach();
+        // cells of hole-free boards far enough from the edges have all their neighbours,
+        // most of the cells of large boards
+        let is_inner = self.holes.is_none()
+            && (reach..self.rows.saturating_sub(reach)).contains(&row)
+            && (reach..self.cols.saturating_sub(reach)).contains(&col);
+        Neighbours {
+            row,
+            col,
+            rows: self.rows,
+            cols: self.cols,
+            topology: self.topology,
+            holes: self.holes.as_deref(),
+            offsets: self.grid.offsets(row),
+            is_inner,
+            // offsets may meet at the same cell on joined boards smaller than the neighbourhood
+            is_narrow: self.topology == Topology::Toroidal
+                && (self.rows <= 2 * reach || self.cols <= 2 * reach),
+            next: 0,
+        }
+    }
+}
+
+impl Field for Shape {
+    type Coordinate = (usize, usize);
+    type Neighbours<'a> = Neighbours<'a>;
+
+    fn cells_total(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    fn index(&self, (row, col): (usize, usize)) -> usize {
+        debug_assert!(col < self.cols);
+        row * self.cols + col
+    }
+
+    fn coordinate(&self, index: usize) -> (usize, usize) {
+        (index / self.cols, index % self.cols)
+    }
+
+    fn neighbours(&self, (row, col): (usize, usize)) -> Neighbours<'_> {
+        Shape::neighbours(self, row, col)
+    }
+
+    fn is_hole(&self, index: usize) -> bool {
+        self.holes.as_ref().is_some_and(|holes| holes[index])
+    }
+
+    fn cells_count(&self) -> usize {
+        self.holes.as_ref().map_or(self.cells_total(), |holes| {
+            holes.iter().filter(|is_hole| !**is_hole).count()
+        })
+    }
+}
+
+// Cells of all rows one after another in a single buffer
+#[derive(Clone, Default)]
+pub struct Board {
+    shape: Shape,
+    cells: Vec<Cell>,
+}
+
+impl Board {
+    // All cells are covered and safe
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self::with_shape(rows, cols, Grid::Square, Topology::Bounded)
+    }
+
+    pub fn with_shape(rows: usize, cols: usize, grid: Grid, topology: Topology) -> Self {
+        debug_assert!(grid.fits(rows, topology));
+        Self {
+            shape: Shape {
+                rows,
+                cols,
+                grid,
+                topology,
+                holes: None,
+            },
+            cells: vec![Cell::default(); rows * cols],
+        }
+    }
+
+    // Cuts the holes of the mask of the same size out of the board
+    pub(super) fn set_mask(&mut self, mask: &Mask) {
+        debug_assert_eq!(mask.size(), (self.rows(), self.cols()));
+        let holes = mask.holes();
+        self.shape.holes = holes.contains(&true).then(|| holes.into());
+    }
+
+    pub fn is_hole(&self, cell: (usize, usize)) -> bool {
+        self.shape.is_hole(self.shape.index(cell))
+    }
+
+    pub fn rows(&self) -> usize {
+        self.shape.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.shape.cols
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.shape.grid
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.shape.topology
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    // Cells in row-major order
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    // Cells in the game, holes left out
+    pub fn cells_count(&self) -> usize {
+        self.shape.cells_count()
+    }
+
+    // The shape to go through the neighbours while changing the cells
+    pub(super) fn split_mut(&mut self) -> (&Shape, &mut [Cell]) {
+        (&self.shape, &mut self.cells)
+    }
+
+    pub(super) fn cells_mut(&mut self) -> &mut [Cell] {
+        &mut self.cells
+    }
+
+    pub fn coordinate(&self, index: usize) -> (usize, usize) {
+        self.shape.coordinate(index)
+    }
+
+    pub fn neighbours(&self, row: usize, col: usize) -> Neighbours<'_> {
+        self.shape.neighbours(row, col)
+    }
+}
+
+impl Index<(usize, usize)> for Board {
+    type Output = Cell;
+
+    fn index(&self, cell: (usize, usize)) -> &Cell {
+        &self.cells[self.shape.index(cell)]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Board {
+    fn index_mut(&mut self, cell: (usize, usize)) -> &mut Cell {
+        let index = self.shape.index(cell);
+        &mut self.cells[index]
+    }
+}
+
+pub(super) const OFFSETS: [(isize, isize); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+];
+
+const HEX_EVEN_ROW_OFFSETS: [(isize, isize); 6] =
+    [(0, -1), (0, 1), (-1, -1), (-1, 0), (1, -1), (1, 0)];
+
+const HEX_ODD_ROW_OFFSETS: [(isize, isize); 6] =
+    [(0, -1), (0, 1), (-1, 0), (-1, 1), (1, 0), (1, 1)];
+
+const KNIGHT_OFFSETS: [(isize, isize); 8] = [
+    (1, 2),
+    (2, 1),
+    (-1, 2),
+    (-2, 1),
+    (1, -2),
+    (2, -1),
+    (-1, -2),
+    (-2, -1),
+];
+
+const RADIUS_2_OFFSETS: [(isize, isize); 24] = {
+    let mut offsets = [(0, 0); 24];
+    let mut index = 0;
+    let mut i = 0;
+    while i < 25 {
+        let (dr, dc) = (i / 5 - 2, i % 5 - 2);
+        if dr != 0 || dc != 0 {
+            offsets[index] = (dr, dc);
+            index += 1;
+        }
+        i += 1;
+    }
+    offsets
+};
+
+// Cells around the given one inside the board except holes
+#[derive(Clone)]
+pub struct Neighbours<'a> {
+    row: usize,
+    col: usize,
+    rows: usize,
+    cols: usize,
+    topology: Topology,
+    holes: Option<&'a [bool]>,
+    offsets: &'static [(isize, isize)],
+    is_inner: bool,
+    is_narrow: bool,
+    next: usize,
+}
+
+impl Neighbours<'_> {
+    fn shift(&self, offset: (isize, isize)) -> Option<(usize, usize)> {
+        self.topology
+            .shift((self.row, self.col), offset, (self.rows, self.cols))
+    }
+
+    // Cells near the edges or holes, kept apart so the common case stays small enough to inline
+    #[inline(never)]
+    fn next_checked(&mut self) -> Option<(usize, usize)> {
+        while let Some(&offset) = self.offsets.get(self.next) {
+            self.next += 1;
+            let Some(cell) = self.shift(offset) else {
+                continue;
+            };
+            if self
+                .holes
+                .is_some_and(|holes| holes[cell.0 * self.cols + cell.1])
+            {
+                continue;
+            }
+            let is_repeated = self.is_narrow
+                && (cell == (self.row, self.col)
+                    || self.offsets[..self.next - 1]
+                        .iter()
+                        .any(|&earlier| self.shift(earlier) == Some(cell)));
+            if !is_repeated {
+                return Some(cell);
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for Neighbours<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.is_inner {
+            return self.next_checked();
+        }
+        let &(dr, dc) = self.offsets.get(self.next)?;
+        self.next += 1;
+        Some((
+            self.row.wrapping_add_signed(dr),
+            self.col.wrapping_add_signed(dc),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn board_cell_bits() {
+        let mut cell = Cell::default();
+        assert!(cell.is_safe() && !cell.is_visible() && !cell.is_flagged());
+        cell.set_mines_count(24 * MAX_CELL_MINES);
+        cell.set_mines(1);
+        cell.set_flags(1);
+        cell.set_visible(true);
+        cell.set_flags(0);
+        assert_eq!(cell.mines_count(), 24 * MAX_CELL_MINES);
+        assert!(!cell.is_safe() && cell.is_visible() && !cell.is_flagged());
+        cell.set_mines(MAX_CELL_MINES);
+        cell.set_flags(2);
+        assert_eq!((cell.mines(), cell.flags()), (MAX_CELL_MINES, 2));
+        assert_eq!(cell.mines_count(), 24 * MAX_CELL_MINES);
+        assert!(cell.is_visible());
+        assert_eq!(std::mem::size_of::<Cell>(), 2);
+    }
+
+    #[test]
+    fn board_neighbours() {
+        let mut board = Board::new(3, 4);
+        assert_eq!(board.neighbours(1, 1).count(), 8);
+        assert_eq!(
+            board.neighbours(0, 0).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1), (1, 1)]
+        );
+        assert_eq!(board.neighbours(2, 3).count(), 3);
+        board[(2, 3)].set_flags(1);
+        assert!(board.cells()[11].is_flagged());
+        assert_eq!(board.coordinate(11), (2, 3));
+    }
+
+    #[test]
+    fn board_toroidal_neighbours() {
+        let board = Board::with_shape(3, 4, Grid::Square, Topology::Toroidal);
+        let mut corner = board.neighbours(0, 0).collect::<Vec<_>>();
+        corner.sort();
+        assert_eq!(
+            corner,
+            vec![
+                (0, 1),
+                (0, 3),
+                (1, 0),
+                (1, 1),
+                (1, 3),
+                (2, 0),
+                (2, 1),
+                (2, 3)
+            ]
+        );
+        // opposite neighbours coincide on narrow boards
+        let board = Board::with_shape(2, 4, Grid::Square, Topology::Toroidal);
+        assert_eq!(board.neighbours(0, 0).count(), 5);
+        let board = Board::with_shape(1, 1, Grid::Square, Topology::Toroidal);
+        assert_eq!(board.neighbours(0, 0).count(), 0);
+        assert_eq!("Toroidal".parse::<Topology>(), Ok(Topology::Toroidal));
+        assert!("sphere".parse::<Topology>().is_err());
+    }
+
+    #[test]
+    fn board_hex_neighbours() {
+        let board = Board::with_shape(4, 4, Grid::Hex, Topology::Bounded);
+        let sorted = |row, col| {
+            let mut cells = board.neighbours(row, col).collect::<Vec<_>>();
+            cells.sort();
+            cells
+        };
+        assert_eq!(
+            sorted(1, 1),
+            vec![(0, 1), (0, 2), (1, 0), (1, 2), (2, 1), (2, 2)]
+        );
+        assert_eq!(
+            sorted(2, 1),
+            vec![(1, 0), (1, 1), (2, 0), (2, 2), (3, 0), (3, 1)]
+        );
+        assert_eq!(sorted(0, 0), vec![(0, 1), (1, 0)]);
+        // every cell is a neighbour of its neighbours
+        for index in 0..16 {
+            let cell = board.coordinate(index);
+            for (row, col) in board.neighbours(cell.0, cell.1) {
+                assert!(board.neighbours(row, col).any(|other| other == cell));
+            }
+        }
+        let board = Board::with_shape(4, 4, Grid::Hex, Topology::Toroidal);
+        assert!((0..16).all(|index| {
+            let (row, col) = board.coordinate(index);
+            board.neighbours(row, col).count() == 6
+        }));
+        assert!(!Grid::Hex.fits(3, Topology::Toroidal));
+        assert_eq!("HEX".parse::<Grid>(), Ok(Grid::Hex));
+    }
+
+    #[test]
+    fn board_neighbourhoods() {
+        let count = |grid, row, col| {
+            Board::with_shape(7, 7, grid, Topology::Bounded)
+                .neighbours(row, col)
+                .count()
+        };
+        assert_eq!(count(Grid::Knight, 3, 3), 8);
+        assert_eq!(count(Grid::Knight, 0, 0), 2);
+        assert_eq!(count(Grid::Cross, 3, 3), 4);
+        assert_eq!(count(Grid::Cross, 0, 3), 3);
+        assert_eq!(count(Grid::Radius2, 3, 3), 24);
+        assert_eq!(count(Grid::Radius2, 0, 0), 8);
+        let board = Board::with_shape(7, 7, Grid::Knight, Topology::Bounded);
+        assert!(board.neighbours(3, 3).all(|(row, col)| {
+            let (dr, dc) = (row.abs_diff(3), col.abs_diff(3));
+            dr.min(dc) == 1 && dr.max(dc) == 2
+        }));
+        // 4x4 joined board has only 15 other cells for 24 offsets
+        let board = Board::with_shape(4, 4, Grid::Radius2, Topology::Toroidal);
+        assert_eq!(board.neighbours(0, 0).count(), 15);
+        assert_eq!("Radius2".parse::<Grid>(), Ok(Grid::Radius2));
+    }
+
+    #[test]
+    fn board_hole_neighbours() {
+        let mut board = Board::with_shape(3, 3, Grid::Square, Topology::Toroidal);
+        board.set_mask(&Mask::new(3, 3, |row, col| row != 1 || col == 1));
+        assert!(board.is_hole((1, 0)) && !board.is_hole((1, 1)));
+        let mut corner = board.neighbours(0, 0).collect::<Vec<_>>();
+        corner.sort();
+        assert_eq!(corner, vec![(0, 1), (0, 2), (1, 1), (2, 0), (2, 1), (2, 2)]);
+        assert_eq!(board.neighbours(1, 1).count(), 6);
+        // masks without holes keep the board as it is
+        board.set_mask(&Mask::new(3, 3, |_, _| true));
+        assert!(board.shape.holes.is_none());
+    }
+}
@@ -0,0 +1,329 @@
+// Board without edges for the endless mode. Cells are kept in square chunks created when
+// first needed, mines of a chunk depend only on the seed and the chunk position, so the
+// world is the same whatever order it is explored in
+use std::collections::{HashMap, VecDeque};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{Board, Cell, State, OFFSETS};
+
+const CHUNK_SIZE: i64 = 32;
+// Flood fill limit, keeps a click finite on sparse boards where empty areas may not end.
+// Opened empty cells at the border of the limit may be chorded to continue
+const MAX_CASCADE: usize = 100_000;
+
+pub type Coordinate = (i64, i64);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EndlessEvent {
+    // Opened cells, starting from the clicked one and then by distance from it
+    CellsRevealed(Vec<Coordinate>),
+    Flagged(Coordinate),
+    Unflagged(Coordinate),
+    Exploded(Coordinate),
+}
+
+pub struct EndlessModel {
+    seed: u64,
+    density: f64,
+    chunks: HashMap<Coordinate, Board>,
+    state: State,
+    cleared: usize,
+    exploded: Option<Coordinate>,
+}
+
+impl EndlessModel {
+    // Density is the share of mines among cells. The cells around the origin are safe and
+    // the origin is opened
+    pub fn new(seed: u64, density: f64) -> Self {
+        let mut model = Self {
+            seed,
+            density,
+            chunks: HashMap::new(),
+            state: State::InProgress,
+            cleared: 0,
+            exploded: None,
+        };
+        model.open_cell(0, 0);
+        model
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    // Number of opened cells
+    pub fn score(&self) -> usize {
+        self.cleared
+    }
+
+    pub fn is_exploded(&self, row: i64, col: i64) -> bool {
+        self.exploded == Some((row, col))
+    }
+
+    // Cells of chunks not explored yet are covered and safe
+    pub fn get_cell(&self, row: i64, col: i64) -> Cell {
+        let (chunk, cell) = locate(row, col);
+        self.chunks
+            .get(&chunk)
+            .map_or(Cell::default(), |board| board[cell])
+    }
+
+    pub fn open_cell(&mut self, row: i64, col: i64) -> Vec<EndlessEvent> {
+        let is_visible = self.cell_mut(row, col).is_visible();
+        if self.state != State::InProgress || is_visible {
+            return Vec::new();
+        }
+        self.open_cells(&[(row, col)])
+    }
+
+    pub fn flag_cell(&mut self, row: i64, col: i64) -> Vec<EndlessEvent> {
+        let is_in_progress = self.state == State::InProgress;
+        let cell = self.cell_mut(row, col);
+        if !is_in_progress || cell.is_visible() {
+            return Vec::new();
+        }
+        cell.set_flags(!cell.is_flagged() as usize);
+        if cell.is_flagged() {
+            vec![EndlessEvent::Flagged((row, col))]
+        } else {
+            vec![EndlessEvent::Unflagged((row, col))]
+        }
+    }
+
+    // Opens covered neighbours of the opened cell if it has as many flags around as mines
+    pub fn chord(&mut self, row: i64, col: i64) -> Vec<EndlessEvent> {
+        let cell = self.get_cell(row, col);
+        if self.state != State::InProgress || !cell.is_visible() {
+            return Vec::new();
+        }
+        let flags = neighbours(row, col)
+            .filter(|&(r, c)| self.get_cell(r, c).is_flagged())
+            .count();
+        let covered = neighbours(row, col)
+            .filter(|&(r, c)| {
+                let cell = self.get_cell(r, c);
+                !cell.is_visible() && !cell.is_flagged()
+            })
+            .collect::<Vec<_>>();
+        if flags != cell.mines_count() || covered.is_empty() {
+            return Vec::new();
+        }
+        self.open_cells(&covered)
+    }
+
+    // Opens the cells flood filling empty areas, the first mine among them ends the game
+    fn open_cells(&mut self, cells: &[Coordinate]) -> Vec<EndlessEvent> {
+        let mut revealed = Vec::new();
+        let mut exploded = None;
+        let mut adjacent = VecDeque::new();
+        for &(row, col) in cells {
+            let cell = self.cell_mut(row, col);
+            cell.set_flags(0);
+            if !cell.is_safe() {
+                exploded = exploded.or(Some((row, col)));
+                continue;
+            }
+            if self.reveal(row, col) {
+                revealed.push((row, col));
+                adjacent.push_back((row, col));
+            }
+        }
+        // breadth first, so cells are listed in order of distance from the opened ones
+        while let Some((row, col)) = adjacent.pop_front() {
+            if self.get_cell(row, col).mines_count() > 0 || revealed.len() >= MAX_CASCADE {
+                continue;
+            }
+            for (r, c) in neighbours(row, col) {
+                let cell = self.cell_mut(r, c);
+                if !cell.is_safe() || cell.is_visible() || cell.is_flagged() {
+                    continue;
+                }
+                if self.reveal(r, c) {
+                    revealed.push((r, c));
+                    adjacent.push_back((r, c));
+                }
+            }
+        }
+        let mut events = Vec::new();
+        if !revealed.is_empty() {
+            events.push(EndlessEvent::CellsRevealed(revealed));
+        }
+        if let Some(cell) = exploded {
+            self.state = State::Lose;
+            self.exploded = Some(cell);
+            events.push(EndlessEvent::Exploded(cell));
+        }
+        events
+    }
+
+    // Counts mines around the safe cell and opens it, false if it was opened already
+    fn reveal(&mut self, row: i64, col: i64) -> bool {
+        if self.get_cell(row, col).is_visible() {
+            return false;
+        }
+        let mines = neighbours(row, col)
+            .filter(|&(r, c)| !self.cell_mut(r, c).is_safe())
+            .count();
+        let cell = self.cell_mut(row, col);
+        cell.set_mines_count(mines);
+        cell.set_visible(true);
+        self.cleared += 1;
+        true
+    }
+
+    // Generates the chunk of the cell if needed
+    fn cell_mut(&mut self, row: i64, col: i64) -> &mut Cell {
+        let (chunk, cell) = locate(row, col);
+        let (seed, density) = (self.seed, self.density);
+        let board = self
+            .chunks
+            .entry(chunk)
+            .or_insert_with(|| generate_chunk(seed, density, chunk));
+        &mut board[cell]
+    }
+}
+
+pub fn neighbours(row: i64, col: i64) -> impl Iterator<Item = Coordinate> {
+    OFFSETS
+        .iter()
+        .map(move |&(dr, dc)| (row + dr as i64, col + dc as i64))
+}
+
+// Chunk containing the cell and the cell position inside it
+fn locate(row: i64, col: i64) -> (Coordinate, (usize, usize)) {
+    let chunk = (row.div_euclid(CHUNK_SIZE), col.div_euclid(CHUNK_SIZE));
+    let cell = (
+        row.rem_euclid(CHUNK_SIZE) as usize,
+        col.rem_euclid(CHUNK_SIZE) as usize,
+    );
+    (chunk, cell)
+}
+
+// The same number of mines in every chunk placed by partial shuffle, the cells around
+// the origin are left free
+fn generate_chunk(seed: u64, density: f64, chunk: Coordinate) -> Board {
+    let size = CHUNK_SIZE as usize;
+    let mut board = Board::new(size, size);
+    let mut rng = StdRng::seed_from_u64(chunk_seed(seed, chunk));
+    let mut candidates = (0..size * size)
+        .filter(|&index| {
+            let (row, col) = board.coordinate(index);
+            let row = chunk.0 * CHUNK_SIZE + row as i64;
+            let col = chunk.1 * CHUNK_SIZE + col as i64;
+            row.abs() > 1 || col.abs() > 1
+        })
+        .collect::<Vec<_>>();
+    let mines = ((density * (size * size) as f64).round() as usize).min(candidates.len());
+    for i in 0..mines {
+        let j = rng.gen_range(i..candidates.len());
+        candidates.swap(i, j);
+        board.cells_mut()[candidates[i]].set_mines(1);
+    }
+    board
+}
+
+// Mixes the chunk position into the seed with splitmix64 steps
+fn chunk_seed(seed: u64, chunk: Coordinate) -> u64 {
+    let mix = |mut value: u64| {
+        value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    };
+    mix(mix(mix(seed) ^ chunk.0 as u64) ^ chunk.1 as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mines_in(model: &mut EndlessModel, rows: Coordinate, cols: Coordinate) -> Vec<Coordinate> {
+        let mut mines = Vec::new();
+        for row in rows.0..rows.1 {
+            for col in cols.0..cols.1 {
+                if !model.cell_mut(row, col).is_safe() {
+                    mines.push((row, col));
+                }
+            }
+        }
+        mines
+    }
+
+    #[test]
+    fn endless_start_and_determinism() {
+        let mut model = EndlessModel::new(7, 0.2);
+        assert!(model.get_cell(0, 0).is_visible());
+        assert_eq!(model.get_cell(0, 0).mines_count(), 0);
+        assert!(model.score() >= 9);
+        assert_eq!(model.state(), State::InProgress);
+        // chunks far away are generated in a different order with the same mines
+        let mut other = EndlessModel::new(7, 0.2);
+        let far = mines_in(&mut model, (-500, -400), (1000, 1100));
+        other.cell_mut(-400, 1100);
+        assert_eq!(mines_in(&mut other, (-500, -400), (1000, 1100)), far);
+        let mut chunk = mines_in(&mut model, (64, 96), (-32, 0));
+        assert_eq!(chunk.len(), 205);
+        chunk.sort();
+        chunk.dedup();
+        assert_eq!(chunk.len(), 205);
+        let mut different = EndlessModel::new(8, 0.2);
+        assert_ne!(mines_in(&mut different, (-500, -400), (1000, 1100)), far);
+    }
+
+    #[test]
+    fn endless_explode_and_chord() {
+        let mut model = EndlessModel::new(3, 0.3);
+        let mine = mines_in(&mut model, (5, 6), (0, 32))[0];
+        let score = model.score();
+        assert_eq!(
+            model.open_cell(mine.0, mine.1),
+            vec![EndlessEvent::Exploded(mine)]
+        );
+        assert_eq!(model.state(), State::Lose);
+        assert!(model.is_exploded(mine.0, mine.1));
+        assert_eq!(model.score(), score);
+        assert!(model.open_cell(100, 100).is_empty());
+
+        // a number next to a single mine, the mine flagged
+        let mut model = EndlessModel::new(3, 0.3);
+        let (number, mine) = (0..32 * 32)
+            .map(|index| (-100 - index / 32, index % 32))
+            .find_map(|(r, c)| {
+                let mines = mines_in(&mut model, (r - 1, r + 2), (c - 1, c + 2));
+                (mines.len() == 1 && mines[0] != (r, c)).then(|| ((r, c), mines[0]))
+            })
+            .unwrap();
+        let (row, col) = mine;
+        model.open_cell(number.0, number.1);
+        assert!(model.chord(number.0, number.1).is_empty());
+        model.flag_cell(row, col);
+        let events = model.chord(number.0, number.1);
+        assert!(matches!(events[..], [EndlessEvent::CellsRevealed(_)]));
+        assert_eq!(model.state(), State::InProgress);
+    }
+
+    #[test]
+    fn endless_cascade_limit() {
+        let mut model = EndlessModel::new(1, 0.0);
+        // the limit is checked before opening neighbours of the next cell
+        assert!((MAX_CASCADE..MAX_CASCADE + 8).contains(&model.score()));
+        // empty cells at the border continue the opening
+        let border = (0..)
+            .map(|col| (0, col))
+            .find(|&(row, col)| !model.get_cell(row, col).is_visible())
+            .unwrap();
+        let events = model.chord(border.0, border.1 - 1);
+        assert!(matches!(events[..], [EndlessEvent::CellsRevealed(_)]));
+        assert!(model.score() > MAX_CASCADE);
+    }
+}
@@ -0,0 +1,182 @@
+// Rules of the game on any board: where the mines go, how numbers are counted and opened cells
+// flood fill, and when the game is won. The board only tells which of its cells touch
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use super::Cell;
+
+// Arrangement of the cells kept in a single buffer, a coordinate for each of them
+pub trait Field {
+    type Coordinate: Copy + PartialEq;
+    type Neighbours<'a>: Iterator<Item = Self::Coordinate>
+    where
+        Self: 'a;
+
+    // Cells in the buffer, holes included
+    fn cells_total(&self) -> usize;
+    fn index(&self, cell: Self::Coordinate) -> usize;
+    fn coordinate(&self, index: usize) -> Self::Coordinate;
+    fn neighbours(&self, cell: Self::Coordinate) -> Self::Neighbours<'_>;
+
+    // Cell at the index is left out of the game
+    fn is_hole(&self, _index: usize) -> bool {
+        false
+    }
+
+    // Cells in the game, holes left out
+    fn cells_count(&self) -> usize {
+        self.cells_total()
+    }
+}
+
+// Partial Fisher-Yates shuffle of the allowed cells, each one repeated as many times
+// as the mines it may hold: every placement of the mines among them is equally likely,
+// and the time doesn't depend on the density
+pub(super) fn fill_mines<F: Field>(
+    field: &F,
+    cells: &mut [Cell],
+    rng: &mut StdRng,
+    mines: usize,
+    slots: usize,
+    excluded: &[F::Coordinate],
+) {
+    let mut candidates = (0..field.cells_total() * slots)
+        .filter(|&slot| {
+            let index = slot / slots;
+            !field.is_hole(index) && !excluded.contains(&field.coordinate(index))
+        })
+        .collect::<Vec<_>>();
+    for i in 0..mines.min(candidates.len()) {
+        let j = rng.gen_range(i..candidates.len());
+        candidates.swap(i, j);
+        let cell = &mut cells[candidates[i] / slots];
+        cell.set_mines(cell.mines() + 1);
+    }
+}
+
+// Keeps the clicked cell and if possible its neighbours free of mines
+pub(super) fn place_mines_around<F: Field>(
+    field: &F,
+    cells: &mut [Cell],
+    rng: &mut StdRng,
+    mines: usize,
+    slots: usize,
+    cell: F::Coordinate,
+) {
+    let mut excluded = field.neighbours(cell).collect::<Vec<_>>();
+    excluded.push(cell);
+    if (field.cells_count() - excluded.len()) * slots < mines {
+        excluded = vec![cell];
+    }
+    fill_mines(field, cells, rng, mines, slots, &excluded);
+    fill_safe_numbers(field, cells);
+}
+
+// Every mine increments the counters around it
+pub(super) fn fill_safe_numbers<F: Field>(field: &F, cells: &mut [Cell]) {
+    for cell in cells.iter_mut() {
+        cell.set_mines_count(0);
+    }
+    for index in 0..cells.len() {
+        let mines = cells[index].mines();
+        if mines == 0 {
+            continue;
+        }
+        for neighbour in field.neighbours(field.coordinate(index)) {
+            let cell = &mut cells[field.index(neighbour)];
+            cell.set_mines_count(cell.mines_count() + mines);
+        }
+    }
+}
+
+// Covered neighbours a chord on the opened number opens, none unless it has as many flags
+// around as the mines it counts
+pub(super) fn chord_cells<F: Field>(
+    field: &F,
+    cells: &[Cell],
+    cell: F::Coordinate,
+) -> Vec<F::Coordinate> {
+    let current = cells[field.index(cell)];
+    if !current.is_visible() || current.mines_count() == 0 {
+        return Vec::new();
+    }
+    let flags = field
+        .neighbours(cell)
+        .map(|neighbour| cells[field.index(neighbour)].flags())
+        .sum::<usize>();
+    if flags != current.mines_count() {
+        return Vec::new();
+    }
+    field
+        .neighbours(cell)
+        .filter(|&neighbour| {
+            let cell = cells[field.index(neighbour)];
+            !cell.is_visible() && !cell.is_flagged()
+        })
+        .collect()
+}
+
+// Opens the cells flood filling empty areas. Returns the opened cells, starting from the
+// first given one and then by distance from it, and the first mine among the given cells
+pub(super) fn open_cells<F: Field>(
+    field: &F,
+    cells: &mut [Cell],
+    opened: &[F::Coordinate],
+) -> (Vec<F::Coordinate>, Option<F::Coordinate>) {
+    let mut revealed = Vec::new();
+    let mut exploded = None;
+    for &cell in opened {
+        let current = &mut cells[field.index(cell)];
+        current.set_flags(0);
+        if !current.is_safe() {
+            exploded = exploded.or(Some(cell));
+            continue;
+        }
+        if current.is_visible() {
+            continue;
+        }
+        current.set_visible(true);
+        revealed.push(cell);
+        if current.mines_count() > 0 {
+            continue;
+        }
+        // breadth first, so cells are listed in order of distance from the opened one
+        let mut adjacent = VecDeque::from([cell]);
+        while let Some(empty) = adjacent.pop_front() {
+            for neighbour in field.neighbours(empty) {
+                let cell = &mut cells[field.index(neighbour)];
+                if !cell.is_safe() || cell.is_visible() {
+                    continue;
+                }
+                cell.set_visible(true);
+                revealed.push(neighbour);
+                if cell.mines_count() == 0 {
+                    adjacent.push_back(neighbour);
+                }
+            }
+        }
+    }
+    (revealed, exploded)
+}
+
+// All safe cells are opened or every mine has its flags
+pub(super) fn is_won<F: Field>(field: &F, cells: &[Cell]) -> bool {
+    let mut is_flag_win = true;
+    let mut is_open_win = true;
+    for (index, cell) in cells.iter().enumerate() {
+        if field.is_hole(index) {
+            continue;
+        }
+        if cell.is_safe() {
+            is_open_win &= cell.is_visible() && !cell.is_flagged();
+        } else {
+            is_flag_win &= cell.flags() == cell.mines();
+        }
+        if !is_flag_win && !is_open_win {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,444 @@
+// Board of several stacked layers for the 3D mode. Every cell touches the cells around it
+// in its own layer and the nine cells facing it in each adjacent layer, up to 26 in total
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::{
+    check_board, chord_cells, fill_mines, fill_safe_numbers, is_won, open_cells,
+    place_mines_around, Cell, Field, Grid, State, Topology,
+};
+
+// Layer, row and column of a cell
+pub type Coordinate = (usize, usize, usize);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum LayeredEvent {
+    // Opened cells, starting from the clicked one and then by distance from it
+    CellsRevealed(Vec<Coordinate>),
+    Flagged(Coordinate),
+    Unflagged(Coordinate),
+    Exploded(Coordinate),
+    Won,
+    Restarted,
+}
+
+// Size of the stacked layers, tells which of their cells touch. Cells of all layers are kept
+// one after another, each layer row by row
+#[derive(Clone, Copy, Default)]
+pub struct Layers {
+    size: Coordinate,
+}
+
+impl Layers {
+    pub fn contains(&self, (layer, row, col): Coordinate) -> bool {
+        let (layers, rows, cols) = self.size;
+        layer < layers && row < rows && col < cols
+    }
+}
+
+impl Field for Layers {
+    type Coordinate = Coordinate;
+    type Neighbours<'a> = LayeredNeighbours;
+
+    fn cells_total(&self) -> usize {
+        let (layers, rows, cols) = self.size;
+        layers * rows * cols
+    }
+
+    fn index(&self, (layer, row, col): Coordinate) -> usize {
+        let (_, rows, cols) = self.size;
+        (layer * rows + row) * cols + col
+    }
+
+    fn coordinate(&self, index: usize) -> Coordinate {
+        let (_, rows, cols) = self.size;
+        (index / (rows * cols), index / cols % rows, index % cols)
+    }
+
+    fn neighbours(&self, cell: Coordinate) -> LayeredNeighbours {
+        LayeredNeighbours {
+            cell,
+            size: self.size,
+            next: 0,
+        }
+    }
+}
+
+// Cells of the 3x3x3 cube around the given one inside the layers, the layer above first
+// and row by row in each layer. Doesn't borrow the layers
+#[derive(Clone)]
+pub struct LayeredNeighbours {
+    cell: Coordinate,
+    size: Coordinate,
+    next: usize,
+}
+
+// Index of the given cell in the middle of the cube
+const CUBE_CENTER: usize = 13;
+
+impl Iterator for LayeredNeighbours {
+    type Item = Coordinate;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (layer, row, col) = self.cell;
+        let (layers, rows, cols) = self.size;
+        // one step back, none or one step forward along the axis, None beyond the edge
+        let step = |value: usize, step: usize, limit: usize| {
+            (value + step).checked_sub(1).filter(|&value| value < limit)
+        };
+        while self.next < 27 {
+            let offset = self.next;
+            self.next += 1;
+            if offset == CUBE_CENTER {
+                continue;
+            }
+            let layer = step(layer, offset / 9, layers);
+            let row = step(row, offset / 3 % 3, rows);
+            let col = step(col, offset % 3, cols);
+            if let (Some(layer), Some(row), Some(col)) = (layer, row, col) {
+                return Some((layer, row, col));
+            }
+        }
+        None
+    }
+}
+
+pub struct LayeredModel {
+    layers: Layers,
+    cells: Vec<Cell>,
+    state: State,
+    mines: usize,
+    rng: StdRng,
+    is_first_click_safe: bool,
+    // mines are placed by the first opened cell
+    is_mines_pending: bool,
+    exploded: Option<Coordinate>,
+}
+
+impl Default for LayeredModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayeredModel {
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
+        Self {
+            layers: Layers::default(),
+            cells: Vec::new(),
+            state: State::InProgress,
+            mines: 0,
+            rng,
+            is_first_click_safe: false,
+            is_mines_pending: false,
+            exploded: None,
+        }
+    }
+
+    // The first opened cell and its neighbours never have mines, applies from the next start
+    pub fn set_first_click_safe(&mut self, is_safe: bool) {
+        self.is_first_click_safe = is_safe;
+    }
+
+    pub fn start(
+        &mut self,
+        (layers, rows, cols): (usize, usize, usize),
+        mines: usize,
+    ) -> Result<(), String> {
+        check_board(
+            layers * rows * cols,
+            rows,
+            mines,
+            1,
+            Grid::Square,
+            Topology::Bounded,
+        )?;
+        self.layers = Layers {
+            size: (layers, rows, cols),
+        };
+        self.cells = vec![Cell::default(); self.layers.cells_total()];
+        self.mines = mines;
+        self.state = State::InProgress;
+        self.exploded = None;
+        self.is_mines_pending = self.is_first_click_safe;
+        if !self.is_mines_pending {
+            fill_mines(&self.layers, &mut self.cells, &mut self.rng, mines, 1, &[]);
+            fill_safe_numbers(&self.layers, &mut self.cells);
+        }
+        Ok(())
+    }
+
+    pub fn restart(&mut self) -> Vec<LayeredEvent> {
+        // nothing to restart before the first start
+        if self.start(self.size(), self.mines).is_err() {
+            return Vec::new();
+        }
+        vec![LayeredEvent::Restarted]
+    }
+
+    // Layers, rows and columns
+    pub fn size(&self) -> (usize, usize, usize) {
+        self.layers.size
+    }
+
+    pub fn mines_count(&self) -> usize {
+        self.mines
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn get_cell(&self, cell: Coordinate) -> Cell {
+        self.cells[self.layers.index(cell)]
+    }
+
+    pub fn is_exploded(&self, cell: Coordinate) -> bool {
+        self.exploded == Some(cell)
+    }
+
+    // Cells around in the same layer and the facing cell with the cells around it in the
+    // layers above and below
+    pub fn neighbours(&self, cell: Coordinate) -> LayeredNeighbours {
+        self.layers.neighbours(cell)
+    }
+
+    pub fn open_cell(&mut self, cell: Coordinate) -> Vec<LayeredEvent> {
+        if !self.can_touch_cell(cell) {
+            return Vec::new();
+        }
+        if self.is_mines_pending {
+            let rng = &mut self.rng;
+            place_mines_around(&self.layers, &mut self.cells, rng, self.mines, 1, cell);
+            self.is_mines_pending = false;
+        }
+        self.open_cells(&[cell])
+    }
+
+    pub fn flag_cell(&mut self, cell: Coordinate) -> Vec<LayeredEvent> {
+        if !self.can_touch_cell(cell) {
+            return Vec::new();
+        }
+        let current = self.cell_mut(cell);
+        current.set_flags(!current.is_flagged() as usize);
+        let mut events = if current.is_flagged() {
+            vec![LayeredEvent::Flagged(cell)]
+        } else {
+            vec![LayeredEvent::Unflagged(cell)]
+        };
+        events.extend(self.track_win_state());
+        events
+    }
+
+    // Opens covered neighbours of the opened number if it has as many flags around
+    pub fn chord(&mut self, cell: Coordinate) -> Vec<LayeredEvent> {
+        if self.state != State::InProgress || !self.layers.contains(cell) {
+            return Vec::new();
+        }
+        let covered = chord_cells(&self.layers, &self.cells, cell);
+        if covered.is_empty() {
+            return Vec::new();
+        }
+        self.open_cells(&covered)
+    }
+
+    fn open_cells(&mut self, cells: &[Coordinate]) -> Vec<LayeredEvent> {
+        let (revealed, exploded) = open_cells(&self.layers, &mut self.cells, cells);
+        let mut events = Vec::new();
+        if !revealed.is_empty() {
+            events.push(LayeredEvent::CellsRevealed(revealed));
+        }
+        match exploded {
+            Some(cell) => {
+                self.exploded = Some(cell);
+                self.state = State::Lose;
+                events.push(LayeredEvent::Exploded(cell));
+            }
+            None => events.extend(self.track_win_state()),
+        }
+        events
+    }
+
+    fn can_touch_cell(&self, cell: Coordinate) -> bool {
+        self.state == State::InProgress
+            && self.layers.contains(cell)
+            && !self.get_cell(cell).is_visible()
+    }
+
+    fn track_win_state(&mut self) -> Option<LayeredEvent> {
+        if self.is_mines_pending || !is_won(&self.layers, &self.cells) {
+            return None;
+        }
+        self.state = State::Win;
+        Some(LayeredEvent::Won)
+    }
+
+    fn cell_mut(&mut self, cell: Coordinate) -> &mut Cell {
+        &mut self.cells[self.layers.index(cell)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Layers of rows of '.' for safe cells and '*' for mines, separated by '|'
+    fn parse(text: &str) -> LayeredModel {
+        let layers = text
+            .split('|')
+            .map(|layer| layer.split_whitespace().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let size = (layers.len(), layers[0].len(), layers[0][0].len());
+        let mut model = LayeredModel::with_seed(0);
+        model.start(size, 0).unwrap();
+        for (layer, rows) in layers.iter().enumerate() {
+            for (row, line) in rows.iter().enumerate() {
+                for (col, ch) in line.chars().enumerate() {
+                    if ch == '*' {
+                        model.cell_mut((layer, row, col)).set_mines(1);
+                        model.mines += 1;
+                    }
+                }
+            }
+        }
+        fill_safe_numbers(&model.layers, &mut model.cells);
+        model
+    }
+
+    fn coordinates(model: &LayeredModel) -> impl Iterator<Item = Coordinate> + '_ {
+        (0..model.layers.cells_total()).map(|index| model.layers.coordinate(index))
+    }
+
+    // Chebyshev distance, the number of steps between cells through neighbours
+    fn distance(a: Coordinate, b: Coordinate) -> usize {
+        a.0.abs_diff(b.0)
+            .max(a.1.abs_diff(b.1))
+            .max(a.2.abs_diff(b.2))
+    }
+
+    #[test]
+    fn layered_neighbours() {
+        let model = parse("... ... ...|... ... ...|... ... ...");
+        assert_eq!(model.size(), (3, 3, 3));
+        assert_eq!(model.neighbours((1, 1, 1)).count(), 26);
+        assert_eq!(model.neighbours((0, 0, 0)).count(), 7);
+        assert_eq!(model.neighbours((1, 0, 1)).count(), 17);
+        let mut around = model.neighbours((1, 1, 1)).collect::<Vec<_>>();
+        around.sort();
+        around.dedup();
+        assert_eq!(around.len(), 26);
+        assert!(around.iter().all(|&cell| distance(cell, (1, 1, 1)) == 1));
+
+        // a single layer is the classic board
+        let flat = parse("... ... ...");
+        assert_eq!(flat.neighbours((0, 1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn layered_numbers_count_adjacent_layers() {
+        let model = parse("... ... ...|... .*. ...|... ... ...");
+        for cell in coordinates(&model).filter(|&cell| cell != (1, 1, 1)) {
+            assert_eq!(model.get_cell(cell).mines_count(), 1, "{cell:?}");
+        }
+        let model = parse("*..|...|..*");
+        assert_eq!(model.get_cell((1, 0, 1)).mines_count(), 2);
+        assert_eq!(model.get_cell((1, 0, 0)).mines_count(), 1);
+        assert_eq!(model.get_cell((0, 0, 2)).mines_count(), 0);
+    }
+
+    #[test]
+    fn layered_flood_fill_through_layers() {
+        // the only mine is in the centre of the cube
+        let mut model = parse(
+            "..... ..... ..... ..... .....|..... ..... ..... ..... .....|
+             ..... ..... ..*.. ..... .....|..... ..... ..... ..... .....|
+             ..... ..... ..... ..... .....",
+        );
+        let events = model.open_cell((0, 0, 0));
+        let [LayeredEvent::CellsRevealed(revealed), LayeredEvent::Won] = &events[..] else {
+            panic!("unexpected events {events:?}");
+        };
+        assert_eq!(revealed.len(), 124);
+        assert_eq!(revealed[0], (0, 0, 0));
+        let mut unique = revealed.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 124);
+        assert!(!unique.contains(&(2, 2, 2)));
+        // the numbers around the mine are opened, but nothing is opened through them
+        assert_eq!(model.get_cell((2, 2, 1)).mines_count(), 1);
+        assert!(model.get_cell((2, 2, 1)).is_visible());
+    }
+
+    #[test]
+    fn layered_flood_fill_stops_at_numbers() {
+        // the full middle layer keeps the fill in the top layer
+        let mut model = parse(".... .... ....|**** **** ****|.... .... ....");
+        assert_eq!(model.get_cell((0, 0, 0)).mines_count(), 4);
+        let events = model.open_cell((2, 1, 1));
+        assert_eq!(events, vec![LayeredEvent::CellsRevealed(vec![(2, 1, 1)])]);
+
+        // an empty layer between two others opens the facing cells of both
+        let mut model = parse("*... .... ....|.... .... ....|.... .... ...*");
+        assert_eq!(model.get_cell((1, 2, 0)).mines_count(), 0);
+        let events = model.open_cell((1, 2, 0));
+        let LayeredEvent::CellsRevealed(revealed) = &events[0] else {
+            panic!("unexpected events {events:?}");
+        };
+        assert_eq!(revealed[0], (1, 2, 0));
+        assert!(revealed.contains(&(0, 2, 0)));
+        assert!(revealed.contains(&(2, 0, 3)));
+        assert_eq!(model.state(), State::Win);
+    }
+
+    #[test]
+    fn layered_chord_and_explosion() {
+        let mut model = parse("*.. ...|... ...");
+        model.open_cell((1, 1, 1));
+        assert_eq!(model.get_cell((1, 1, 1)).mines_count(), 1);
+        assert!(model.chord((1, 1, 1)).is_empty());
+        assert_eq!(
+            model.flag_cell((0, 0, 0)),
+            vec![LayeredEvent::Flagged((0, 0, 0)), LayeredEvent::Won]
+        );
+
+        let mut model = parse("*.. ...|... ...");
+        model.open_cell((1, 1, 1));
+        model.flag_cell((1, 0, 0));
+        let events = model.chord((1, 1, 1));
+        assert_eq!(events.last(), Some(&LayeredEvent::Exploded((0, 0, 0))));
+        assert_eq!(model.state(), State::Lose);
+        assert!(model.is_exploded((0, 0, 0)));
+        assert!(model.open_cell((0, 2, 2)).is_empty());
+    }
+
+    #[test]
+    fn layered_first_click_safe() {
+        for seed in 0..20 {
+            let mut model = LayeredModel::with_seed(seed);
+            model.set_first_click_safe(true);
+            model.start((4, 4, 4), 30).unwrap();
+            model.open_cell((1, 1, 1));
+            assert_eq!(model.get_cell((1, 1, 1)).mines_count(), 0);
+            let mines = coordinates(&model)
+                .filter(|&cell| !model.get_cell(cell).is_safe())
+                .count();
+            assert_eq!(mines, 30);
+        }
+        let mut model = LayeredModel::with_seed(1);
+        assert!(model.start((2, 2, 2), 8).is_err());
+        assert_eq!(model.size(), (0, 0, 0));
+        assert!(model.restart().is_empty());
+        model.start((2, 2, 2), 7).unwrap();
+        assert_eq!(model.restart(), vec![LayeredEvent::Restarted]);
+        assert_eq!(model.size(), (2, 2, 2));
+    }
+}
@@ -0,0 +1,173 @@
+// Text representation of a game, one line per board row:
+//   .  covered safe cell      *  covered mine
+//   f  flagged safe cell      F  flagged mine
+//   o  opened safe cell       X  exploded mine, the game is lost
+//   2-7  covered cell holding that many mines
+//   -  hole, not part of the board
+// Optional `grid <name>` and `topology <name>` lines before the rows choose the cell shape
+// and the edges, see `Grid` and `Topology`, `cell_mines <count>` lets cells hold up to
+// `count` mines.
+// Empty lines and lines starting with '#' are ignored
+use super::{Board, GameModel, Grid, Mask, State, Topology, MAX_CELL_MINES};
+
+pub fn parse(text: &str) -> Result<GameModel, String> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>();
+    let mut grid = Grid::Square;
+    let mut topology = Topology::Bounded;
+    let mut max_cell_mines = 1;
+    // rows never contain spaces
+    while let Some((key, value)) = lines.first().and_then(|line| line.split_once(' ')) {
+        match key {
+            "grid" => grid = value.trim().parse()?,
+            "topology" => topology = value.trim().parse()?,
+            "cell_mines" => {
+                max_cell_mines = value
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|count| (1..=MAX_CELL_MINES).contains(count))
+                    .ok_or_else(|| format!("Invalid mines per cell '{value}'"))?;
+            }
+            _ => return Err(format!("Unknown option '{key}'")),
+        }
+        lines.remove(0);
+    }
+    let col_count = lines.first().map_or(0, |line| line.chars().count());
+    if col_count == 0 {
+        return Err("Layout is empty".to_string());
+    }
+    if !grid.fits(lines.len(), topology) {
+        return Err("Toroidal hex board needs an even number of rows".to_string());
+    }
+    let mut model = GameModel::new();
+    model.set_grid(grid);
+    model.set_topology(topology);
+    model.set_max_cell_mines(max_cell_mines);
+    model.board = Board::with_shape(lines.len(), col_count, grid, topology);
+    let mut exploded = None;
+    let mut holes = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        if line.chars().count() != col_count {
+            return Err(format!("Row {} has unexpected length", row + 1));
+        }
+        for (col, ch) in line.chars().enumerate() {
+            let cell = &mut model.board[(row, col)];
+            let mines = ch.to_digit(10).map_or(0, |digit| digit as usize);
+            match ch {
+                '.' => {}
+                '-' => holes.push((row, col)),
+                '*' => cell.set_mines(1),
+                'f' => cell.set_flags(1),
+                'F' => {
+                    cell.set_mines(1);
+                    cell.set_flags(1);
+                }
+                'o' => {
+                    cell.set_visible(true);
+                    model.given_cells.push((row, col));
+                }
+                'X' => {
+                    cell.set_mines(1);
+                    exploded = Some((row, col));
+                }
+                '2'..='7' if mines <= max_cell_mines => cell.set_mines(mines),
+                _ => return Err(format!("Unexpected symbol '{ch}' at row {}", row + 1)),
+            }
+            model.mines += cell.mines();
+        }
+    }
+    if !holes.is_empty() {
+        let mask = Mask::new(lines.len(), col_count, |row, col| {
+            !holes.contains(&(row, col))
+        });
+        model.board.set_mask(&mask);
+        model.mask = Some(mask);
+    }
+    model.fill_safe_numbers();
+    if let Some(last_step) = exploded {
+        model.last_step = Some(last_step);
+        model.state = State::Lose;
+    } else {
+        model.track_win_state();
+    }
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_parse() {
+        let model = parse(
+            "# sample
+            .*o
+            fFo
+            ",
+        )
+        .unwrap();
+        assert_eq!(model.board_size(), (2, 3));
+        assert_eq!(model.mines_count(), 2);
+        assert_eq!(model.state(), State::InProgress);
+        assert!(model.get_cell(0, 2).is_visible());
+        assert_eq!(model.get_cell(0, 2).mines_count(), 2);
+        assert!(model.get_cell(1, 0).is_flagged());
+        assert!(model.get_cell(1, 0).is_safe());
+        assert_eq!(model.given_cells(), &[(0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn layout_parse_lost_game() {
+        let model = parse("oX\n*o").unwrap();
+        assert_eq!(model.state(), State::Lose);
+        assert!(model.is_last_step(0, 1));
+    }
+
+    #[test]
+    fn layout_parse_topology() {
+        let model = parse("topology toroidal\n*..\n...\n..o").unwrap();
+        assert_eq!(model.topology(), Topology::Toroidal);
+        assert_eq!(model.get_cell(2, 2).mines_count(), 1);
+        assert!(parse("topology flat\n..").is_err());
+        assert!(parse("size 2\n..").is_err());
+
+        let model = parse("grid hex\ntopology toroidal\n*.\n..").unwrap();
+        assert_eq!(model.grid(), Grid::Hex);
+        assert!(parse("grid hex\ntopology toroidal\n*.\n..\n..").is_err());
+    }
+
+    #[test]
+    fn layout_parse_cell_mines() {
+        let model = parse("cell_mines 3\n3.o\n*F.").unwrap();
+        assert_eq!(model.max_cell_mines(), 3);
+        assert_eq!(model.mines_count(), 5);
+        assert_eq!(model.get_cell(0, 0).mines(), 3);
+        assert_eq!(model.get_cell(0, 2).mines_count(), 1);
+        assert_eq!(model.get_cell(1, 1).mines_count(), 4);
+        assert!(parse("2.\n..").is_err());
+        assert!(parse("cell_mines 2\n3.").is_err());
+        assert!(parse("cell_mines 9\n..").is_err());
+    }
+
+    #[test]
+    fn layout_parse_holes() {
+        let model = parse("*-o\n.-.").unwrap();
+        assert!(model.is_hole(0, 1) && !model.is_hole(0, 0));
+        assert_eq!(model.mask().map(|mask| mask.cells_count()), Some(4));
+        // the mine is beyond the hole
+        assert_eq!(model.get_cell(0, 2).mines_count(), 0);
+        assert_eq!(model.get_cell(1, 0).mines_count(), 1);
+        assert!(parse("*.o\n...").unwrap().mask().is_none());
+    }
+
+    #[test]
+    fn layout_parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("..\n.").is_err());
+        assert!(parse(".?").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+// Shape of a board. Holes are left out of the game: they hold no mines, can't be opened
+// and aren't neighbours of any cell. Text masks have one line per row:
+//   .  cell      # or space  hole
+// Shorter rows end with holes, lines starting with ';' are ignored
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mask {
+    rows: usize,
+    cols: usize,
+    holes: Vec<bool>,
+}
+
+impl Mask {
+    // Mask of the size with cells where the predicate holds
+    pub fn new(rows: usize, cols: usize, is_cell: impl Fn(usize, usize) -> bool) -> Self {
+        let holes = (0..rows * cols)
+            .map(|index| !is_cell(index / cols, index % cols))
+            .collect();
+        Self { rows, cols, holes }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let lines = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.starts_with(';'))
+            .collect::<Vec<_>>();
+        // empty lines around the shape are not part of it
+        let first = lines.iter().position(|line| !line.is_empty()).unwrap_or(0);
+        let last = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |i| i + 1);
+        let lines = &lines[first..last];
+        let cols = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut holes = vec![true; lines.len() * cols];
+        for (row, line) in lines.iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                match ch {
+                    '.' => holes[row * cols + col] = false,
+                    '#' | ' ' => {}
+                    _ => return Err(format!("Unexpected symbol '{ch}' at row {}", row + 1)),
+                }
+            }
+        }
+        let mask = Self {
+            rows: lines.len(),
+            cols,
+            holes,
+        };
+        if mask.cells_count() == 0 {
+            return Err("Mask has no cells".to_string());
+        }
+        Ok(mask)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn is_hole(&self, row: usize, col: usize) -> bool {
+        self.holes[row * self.cols + col]
+    }
+
+    // Cells of the board left for the game
+    pub fn cells_count(&self) -> usize {
+        self.holes.iter().filter(|is_hole| !**is_hole).count()
+    }
+
+    // Holes in row-major order
+    pub(super) fn holes(&self) -> &[bool] {
+        &self.holes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_parse() {
+        let mask = Mask::parse("; ring\n\n###\n.#.\n...\n\n").unwrap();
+        assert_eq!(mask.size(), (3, 3));
+        assert!(mask.is_hole(0, 0) && mask.is_hole(1, 1) && !mask.is_hole(1, 2));
+        assert_eq!(mask.cells_count(), 5);
+
+        let mask = Mask::parse(" .\n.").unwrap();
+        assert_eq!(mask, Mask::new(2, 2, |row, col| row + col == 1));
+
+        assert!(Mask::parse("#\n").is_err());
+        assert!(Mask::parse(".x").is_err());
+    }
+}
@@ -0,0 +1,181 @@
+// Recorded game: board at the start in the layout format followed by moves, one per line:
+//   open <row> <col>
+//   flag <row> <col>
+//   chord <row> <col>
+// Empty lines and lines starting with '#' are ignored
+use super::{layout, GameModel, Grid, Move, Topology};
+
+pub fn to_text(model: &GameModel) -> String {
+    let mut text = "# Minesweeper replay\n".to_string();
+    if model.grid() != Grid::Square {
+        text += &format!("grid {}\n", model.grid().name());
+    }
+    if model.topology() != Topology::Bounded {
+        text += &format!("topology {}\n", model.topology().name());
+    }
+    if model.max_cell_mines() > 1 {
+        text += &format!("cell_mines {}\n", model.max_cell_mines());
+    }
+    let cols = model.board.cols();
+    for (row, cells) in model.board.cells().chunks(cols).enumerate() {
+        let line = cells
+            .iter()
+            .enumerate()
+            .map(|(col, cell)| match cell.mines() {
+                _ if model.is_hole(row, col) => '-',
+                0 if model.given_cells.contains(&(row, col)) => 'o',
+                0 => '.',
+                1 => '*',
+                mines => char::from(b'0' + mines as u8),
+            })
+            .collect::<String>();
+        text += &line;
+        text.push('\n');
+    }
+    for action in &model.moves {
+        let line = match action {
+            Move::Open(row, col) => format!("open {row} {col}\n"),
+            Move::Flag(row, col) => format!("flag {row} {col}\n"),
+            Move::Chord(row, col) => format!("chord {row} {col}\n"),
+        };
+        text += &line;
+    }
+    text
+}
+
+// Game with all the moves applied
+pub fn parse(text: &str) -> Result<GameModel, String> {
+    let mut board = Vec::new();
+    let mut moves = Vec::new();
+    for (index, line) in text.lines().map(str::trim).enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+        let action = match parts[..] {
+            ["open", row, col] => parse_cell(row, col).map(|(r, c)| Move::Open(r, c)),
+            ["flag", row, col] => parse_cell(row, col).map(|(r, c)| Move::Flag(r, c)),
+            ["chord", row, col] => parse_cell(row, col).map(|(r, c)| Move::Chord(r, c)),
+            [_] | ["grid" | "topology" | "cell_mines", _] if moves.is_empty() => {
+                board.push(line);
+                continue;
+            }
+            _ => None,
+        };
+        let action =
+            action.ok_or_else(|| format!("Invalid move '{line}' at line {}", index + 1))?;
+        moves.push(action);
+    }
+    let mut model = layout::parse(&board.join("\n"))?;
+    let (rows, cols) = model.board_size();
+    for action in moves {
+        let (row, col) = action.cell();
+        if row >= rows || col >= cols {
+            return Err(format!("Move {action:?} is out of the board"));
+        }
+        model.apply(action);
+    }
+    Ok(model)
+}
+
+fn parse_cell(row: &str, col: &str) -> Option<(usize, usize)> {
+    Some((row.parse().ok()?, col.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_model::{Mask, State};
+
+    #[test]
+    fn replay_round_trip() {
+        let mut model = GameModel::with_seed(5);
+        model.start(6, 7, 8).unwrap();
+        let mine = (0..42)
+            .map(|i| (i / 7, i % 7))
+            .find(|&(r, c)| !model.get_cell(r, c).is_safe())
+            .unwrap();
+        let safe = (0..42)
+            .map(|i| (i / 7, i % 7))
+            .find(|&(r, c)| model.get_cell(r, c).is_safe())
+            .unwrap();
+        model.open_cell(safe.0, safe.1);
+        model.flag_cell(5, 6);
+        model.chord(safe.0, safe.1);
+        model.open_cell(mine.0, mine.1);
+
+        let text = to_text(&model);
+        let loaded = parse(&text).unwrap();
+        assert_eq!(loaded.moves(), model.moves());
+        assert_eq!(loaded.state(), State::Lose);
+        assert!(loaded.is_last_step(mine.0, mine.1));
+        assert_eq!(to_text(&loaded), text);
+    }
+
+    #[test]
+    fn replay_keeps_board_shape() {
+        let mut model = GameModel::with_seed(2);
+        model.set_grid(Grid::Hex);
+        model.set_topology(Topology::Toroidal);
+        model.start(4, 4, 3).unwrap();
+        model.flag_cell(0, 0);
+        let text = to_text(&model);
+        assert!(text.contains("grid hex\ntopology toroidal\n"));
+        let loaded = parse(&text).unwrap();
+        assert_eq!(loaded.grid(), Grid::Hex);
+        assert_eq!(loaded.topology(), Topology::Toroidal);
+        assert_eq!(to_text(&loaded), text);
+    }
+
+    #[test]
+    fn replay_keeps_cell_mines() {
+        let mut model = GameModel::with_seed(4);
+        model.set_max_cell_mines(3);
+        model.start(5, 5, 30).unwrap();
+        model.flag_cell(0, 0);
+        model.flag_cell(0, 0);
+        let text = to_text(&model);
+        assert!(text.contains("cell_mines 3\n"));
+        let loaded = parse(&text).unwrap();
+        assert_eq!(loaded.max_cell_mines(), 3);
+        assert_eq!(loaded.mines_count(), 30);
+        assert_eq!(loaded.get_cell(0, 0).flags(), 2);
+        assert_eq!(to_text(&loaded), text);
+    }
+
+    #[test]
+    fn replay_keeps_holes() {
+        let mut model = GameModel::with_seed(6);
+        model.set_mask(Some(Mask::new(4, 5, |row, col| row != 1 || col < 2)));
+        model.start(4, 5, 3).unwrap();
+        model.flag_cell(3, 4);
+        let text = to_text(&model);
+        assert!(text
+            .lines()
+            .nth(2)
+            .is_some_and(|line| line.ends_with("---")));
+        let loaded = parse(&text).unwrap();
+        assert!(loaded.is_hole(1, 2) && !loaded.is_hole(2, 2));
+        assert_eq!(to_text(&loaded), text);
+    }
+
+    #[test]
+    fn replay_keeps_given_cells() {
+        let mut model = layout::parse("o..\n..*").unwrap();
+        model.open_cell(1, 0);
+        let text = to_text(&model);
+        assert!(text.contains("o..\n..*\nopen 1 0\n"));
+        let loaded = parse(&text).unwrap();
+        assert_eq!(loaded.given_cells(), &[(0, 0)]);
+        assert!(loaded.position_after(0).get_cell(0, 0).is_visible());
+        assert!(!loaded.position_after(0).get_cell(1, 0).is_visible());
+    }
+
+    #[test]
+    fn replay_parse_errors() {
+        assert!(parse("..\n*.\nopen 0").is_err());
+        assert!(parse("..\n*.\nopen 2 0").is_err());
+        assert!(parse("..\nopen 0 0\n*.").is_err());
+        assert!(parse("..\n*.\njump 0 0").is_err());
+    }
+}
@@ -0,0 +1,43 @@
+// Parser of simple text files made of `key = value` lines.
+// Empty lines and lines starting with '#' are ignored
+pub fn parse(text: &str) -> Result<Vec<(String, String)>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Line {}: '=' expected", index + 1))?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(format!("Line {}: key expected", index + 1));
+            }
+            Ok((key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_value_parse() {
+        let pairs = parse("# comment\n\n a = 1 \nb=two words\nc =").unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "two words".to_string()),
+                ("c".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn key_value_parse_errors() {
+        assert!(parse("a = 1\nb").is_err());
+        assert!(parse(" = 1").is_err());
+    }
+}
@@ -0,0 +1,601 @@
+use std::path::PathBuf;
+
+use sdl2::rect::Point;
+
+use crate::audio::{AudioSink, NoAudio, Sound};
+use crate::bindings::{KeyAction, KeyBindings};
+use crate::camera::Camera;
+use crate::game_model::layered::{Coordinate, LayeredEvent, LayeredModel};
+use crate::game_model::State;
+use crate::minesweeper::Size;
+use crate::render::{InputEvent, InputSource, Renderer, Sprite};
+use crate::resources::ColorManager;
+use crate::screen::{self, *};
+
+// Screen of the 3D mode: one layer is played at a time, the layers above and below it are
+// drawn tinted at its left and right, one empty column apart
+pub struct Layered<R: Renderer, I: InputSource> {
+    model: LayeredModel,
+    renderer: R,
+    input: I,
+    color_manager: ColorManager,
+    is_running: bool,
+    camera: Camera,
+    window_size: Size,
+    // the played layer
+    layer: usize,
+    pointer: Pointer<Coordinate>,
+    screenshot_path: Option<PathBuf>,
+    audio: Box<dyn AudioSink>,
+    is_muted: bool,
+    bindings: KeyBindings,
+    // keyboard selected cell of the played layer, shown after it's moved by keys
+    cursor: (usize, usize),
+    is_cursor_visible: bool,
+}
+
+impl<R: Renderer, I: InputSource> Layered<R, I> {
+    pub fn new(
+        model: LayeredModel,
+        renderer: R,
+        input: I,
+        color_manager: ColorManager,
+        mut camera: Camera,
+        window_size: Size,
+    ) -> Self {
+        let (_, rows, cols) = model.size();
+        camera.set_margin(cols + 1);
+        camera.fit((window_size.width, window_size.height), (rows, cols));
+        Self {
+            model,
+            renderer,
+            input,
+            color_manager,
+            is_running: false,
+            camera,
+            window_size,
+            layer: 0,
+            pointer: Pointer::new(),
+            screenshot_path: None,
+            audio: Box::new(NoAudio),
+            is_muted: false,
+            bindings: KeyBindings::default(),
+            cursor: (0, 0),
+            is_cursor_visible: false,
+        }
+    }
+
+    pub fn set_audio(&mut self, audio: Box<dyn AudioSink>, is_muted: bool) {
+        self.audio = audio;
+        self.is_muted = is_muted;
+    }
+
+    pub fn set_bindings(&mut self, bindings: KeyBindings) {
+        self.bindings = bindings;
+    }
+
+    fn play(&mut self, sound: Sound) {
+        if !self.is_muted {
+            self.audio.play(sound);
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), String> {
+        self.is_running = true;
+        screen::run(self)
+    }
+
+    pub fn handle_events(&mut self) {
+        for event in self.input.poll_events() {
+            match event {
+                InputEvent::Quit => self.is_running = false,
+                InputEvent::KeyDown(keycode) => {
+                    if let Some(action) = self.bindings.action(keycode) {
+                        self.handle_action(action);
+                    }
+                }
+                InputEvent::Resized(width, height) => {
+                    self.window_size = Size { height, width };
+                    let (_, rows, cols) = self.model.size();
+                    self.camera.fit((width, height), (rows, cols));
+                }
+                InputEvent::MouseWheel(steps) => {
+                    let state = self.input.mouse_state();
+                    self.camera.zoom(steps, Point::new(state.x, state.y));
+                }
+            }
+        }
+
+        let state = self.input.mouse_state();
+        let cell = self.cell_at(Point::new(state.x, state.y));
+        let can_drag = !self.camera.is_board_visible();
+        let camera = &mut self.camera;
+        let press = self.pointer.update(state, cell, can_drag, |delta| {
+            camera.pan(delta.x(), delta.y())
+        });
+        if let Some(press) = press {
+            if let (Some((layer, row, col)), Some(action)) = (press.cell, press.action()) {
+                // keyboard continues from the clicked cell
+                self.cursor = (row, col);
+                self.is_cursor_visible = false;
+                self.act(action, (layer, row, col));
+            }
+        }
+    }
+
+    // Cell of the played layer under the point
+    fn cell_at(&self, point: Point) -> Option<Coordinate> {
+        self.camera
+            .cell_at(point.x(), point.y())
+            .map(|(row, col)| (self.layer, row, col))
+    }
+
+    // Keys of the classic game without meaning here are ignored
+    fn handle_action(&mut self, action: KeyAction) {
+        let (layers, _, _) = self.model.size();
+        match action {
+            KeyAction::Quit => self.is_running = false,
+            KeyAction::Restart => {
+                let events = self.model.restart();
+                self.handle_model_events(events);
+            }
+            KeyAction::Screenshot => {
+                let path = format!("screenshot_{}.png", timestamp());
+                self.screenshot_path = Some(PathBuf::from(path));
+            }
+            KeyAction::Mute => self.is_muted = !self.is_muted,
+            KeyAction::LayerUp => self.switch_layer(self.layer.saturating_sub(1)),
+            KeyAction::LayerDown => {
+                self.switch_layer((self.layer + 1).min(layers.saturating_sub(1)))
+            }
+            KeyAction::Up => self.move_cursor(-1, 0),
+            KeyAction::Down => self.move_cursor(1, 0),
+            KeyAction::Left => self.move_cursor(0, -1),
+            KeyAction::Right => self.move_cursor(0, 1),
+            KeyAction::NextCovered => self.move_cursor_to_covered(),
+            KeyAction::Open | KeyAction::Flag | KeyAction::Chord => {
+                self.is_cursor_visible = true;
+                let (row, col) = self.cursor;
+                self.act(action, (self.layer, row, col));
+            }
+            _ => {}
+        }
+    }
+
+    // Buttons held over the previous layer don't act on the new one
+    fn switch_layer(&mut self, layer: usize) {
+        if layer != self.layer {
+            self.layer = layer;
+            self.pointer.cancel();
+        }
+    }
+
+    // The first key press only shows the cursor
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        if self.is_cursor_visible {
+            let (_, row_count, col_count) = self.model.size();
+            let (row, col) = self.cursor;
+            self.cursor = (
+                row.saturating_add_signed(rows).min(row_count - 1),
+                col.saturating_add_signed(cols).min(col_count - 1),
+            );
+        }
+        self.show_cursor();
+    }
+
+    // Next covered and not flagged cell of the played layer in reading order, wrapping at
+    // its end
+    fn move_cursor_to_covered(&mut self) {
+        let (_, rows, cols) = self.model.size();
+        let start = self.cursor.0 * cols + self.cursor.1;
+        let next = (1..=rows * cols)
+            .map(|offset| (start + offset) % (rows * cols))
+            .map(|index| (index / cols, index % cols))
+            .find(|&(row, col)| {
+                let cell = self.model.get_cell((self.layer, row, col));
+                !cell.is_visible() && !cell.is_flagged()
+            });
+        if let Some(cell) = next {
+            self.cursor = cell;
+        }
+        self.show_cursor();
+    }
+
+    fn show_cursor(&mut self) {
+        self.is_cursor_visible = true;
+        self.camera.ensure_visible(self.cursor.0, self.cursor.1);
+    }
+
+    // Open, flag or chord of the cell by the mouse or the keyboard
+    fn act(&mut self, action: KeyAction, cell: Coordinate) {
+        let events = match action {
+            KeyAction::Open => self.model.open_cell(cell),
+            KeyAction::Flag => self.model.flag_cell(cell),
+            KeyAction::Chord => self.model.chord(cell),
+            _ => return,
+        };
+        self.handle_model_events(events);
+    }
+
+    fn handle_model_events(&mut self, events: Vec<LayeredEvent>) {
+        for event in events {
+            let sound = match event {
+                LayeredEvent::CellsRevealed(cells) if cells.len() > 1 => Sound::Cascade,
+                LayeredEvent::CellsRevealed(_) => Sound::Reveal,
+                LayeredEvent::Flagged(..) => Sound::Flag,
+                LayeredEvent::Unflagged(..) => Sound::Unflag,
+                LayeredEvent::Exploded(..) => Sound::Explosion,
+                LayeredEvent::Won => Sound::Win,
+                LayeredEvent::Restarted => continue,
+            };
+            self.play(sound);
+        }
+    }
+
+    pub fn draw(&mut self) -> Result<(), String> {
+        self.renderer.clear();
+        let is_lose = self.model.state() == State::Lose;
+        let pressed = self.pressed_cells();
+        let (rows, cols) = self.camera.visible_cells();
+        for row in rows {
+            for col in cols.clone() {
+                let sprite = self.cell_sprite((self.layer, row, col), is_lose, &pressed);
+                self.renderer
+                    .draw_sprite(sprite, self.camera.cell_frame(row, col))?;
+            }
+        }
+        if let Some((_, row, col)) = self.highlighted_cell() {
+            let frame = self.camera.cell_frame(row, col);
+            self.renderer.fill_rect(self.color_manager.hover(), frame)?;
+        }
+        if self.is_cursor_visible {
+            let frame = self.camera.cell_frame(self.cursor.0, self.cursor.1);
+            draw_frame(&mut self.renderer, self.color_manager.cursor(), frame)?;
+        }
+        self.draw_side_layers(is_lose, &pressed)?;
+        let message = match self.model.state() {
+            State::InProgress => None,
+            State::Win => Some("You win"),
+            State::Lose => Some("Game Over"),
+        };
+        if let Some(message) = message {
+            show_message(
+                &mut self.renderer,
+                &self.color_manager,
+                &self.window_size,
+                message,
+            )?;
+        }
+        let (layers, _, _) = self.model.size();
+        let status = format!(
+            "Layer {}/{layers}  Mines: {}",
+            self.layer + 1,
+            self.model.mines_count()
+        );
+        show_status(
+            &mut self.renderer,
+            &self.color_manager,
+            &self.window_size,
+            &status,
+        )?;
+        if let Some(path) = self.screenshot_path.take() {
+            self.renderer.save_screenshot(&path)?;
+            println!("Screenshot saved to {}", path.display());
+        }
+        self.renderer.present();
+        Ok(())
+    }
+
+    // Adjacent layers take the margin columns of the camera, the cells touching the hovered
+    // one are highlighted in them
+    fn draw_side_layers(&mut self, is_lose: bool, pressed: &[Coordinate]) -> Result<(), String> {
+        let (layers, _, cols) = self.model.size();
+        let cols = cols as isize;
+        let (rows, _) = self.camera.visible_cells();
+        let hovered = self.highlighted_cell();
+        for col in self.camera.visible_margin_columns() {
+            let (layer, source) = if col < 0 {
+                (self.layer.checked_sub(1), col + cols + 1)
+            } else {
+                (
+                    Some(self.layer + 1).filter(|&layer| layer < layers),
+                    col - cols - 1,
+                )
+            };
+            // the column between the layers stays empty
+            let Some(layer) = layer.filter(|_| (0..cols).contains(&source)) else {
+                continue;
+            };
+            let source = source as usize;
+            for row in rows.clone() {
+                let frame = self.camera.margin_frame(row, col);
+                let sprite = self.cell_sprite((layer, row, source), is_lose, pressed);
+                self.renderer.draw_sprite(sprite, frame)?;
+                self.renderer.fill_rect(self.color_manager.ghost(), frame)?;
+                let is_touching = hovered.is_some_and(|(_, hovered_row, hovered_col)| {
+                    row.abs_diff(hovered_row) <= 1 && source.abs_diff(hovered_col) <= 1
+                });
+                if is_touching {
+                    self.renderer.fill_rect(self.color_manager.hover(), frame)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Mouse feedback is given only to the game in progress
+    fn is_board_interactive(&self) -> bool {
+        self.model.state() == State::InProgress && !self.pointer.is_dragging()
+    }
+
+    fn hovered_cell(&self) -> Option<Coordinate> {
+        self.cell_at(self.pointer.position())
+    }
+
+    // Covered cell of the played layer under the mouse while no button is held
+    fn highlighted_cell(&self) -> Option<Coordinate> {
+        if self.pointer.is_pressed() || !self.is_board_interactive() {
+            return None;
+        }
+        self.hovered_cell()
+            .filter(|&cell| !self.model.get_cell(cell).is_visible())
+    }
+
+    // Covered cells under the held buttons, a chord presses its neighbours in the adjacent
+    // layers too
+    fn pressed_cells(&self) -> Vec<Coordinate> {
+        if !self.is_board_interactive() {
+            return Vec::new();
+        }
+        let mut cells = self
+            .pointer
+            .depressed_cells(self.hovered_cell(), |cell| self.model.neighbours(cell));
+        cells.retain(|&cell| {
+            let cell = self.model.get_cell(cell);
+            !cell.is_visible() && !cell.is_flagged()
+        });
+        cells
+    }
+
+    fn cell_sprite(&self, cell: Coordinate, is_lose: bool, pressed: &[Coordinate]) -> Sprite {
+        if pressed.contains(&cell) {
+            return Sprite::Empty;
+        }
+        cell_sprite(
+            self.model.get_cell(cell),
+            is_lose,
+            self.model.is_exploded(cell),
+        )
+    }
+}
+
+impl<R: Renderer, I: InputSource> Screen for Layered<R, I> {
+    fn handle_events(&mut self) {
+        self.handle_events();
+    }
+
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    fn draw(&mut self) -> Result<(), String> {
+        self.draw()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::RecordingAudio;
+    use crate::render::{DrawCommand, MouseSnapshot, RecordingRenderer, ScriptedInput};
+    use sdl2::keyboard::Keycode;
+    use sdl2::mouse::MouseButton;
+    use std::collections::HashSet;
+
+    const CELL: u32 = 32;
+
+    // Three layers of 3x4 cells, the window fits the played layer and both side ones
+    fn create_game(model: LayeredModel) -> Layered<RecordingRenderer, ScriptedInput> {
+        Layered::new(
+            model,
+            RecordingRenderer::default(),
+            ScriptedInput::default(),
+            ColorManager::default(),
+            Camera::new(16, 128),
+            Size {
+                height: CELL * 3,
+                width: CELL * (3 * 4 + 2),
+            },
+        )
+    }
+
+    fn click(
+        game: &mut Layered<RecordingRenderer, ScriptedInput>,
+        button: MouseButton,
+        row: usize,
+        col: usize,
+    ) {
+        let point = game.camera.cell_frame(row, col).center();
+        game.input.mouse.x = point.x();
+        game.input.mouse.y = point.y();
+        game.input.mouse.buttons = HashSet::from([button]);
+        game.handle_events();
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+    }
+
+    fn sprite_at(
+        game: &Layered<RecordingRenderer, ScriptedInput>,
+        row: usize,
+        col: isize,
+    ) -> Option<Sprite> {
+        let point = game.camera.margin_frame(row, col).center();
+        game.renderer.sprite_at(point.x(), point.y())
+    }
+
+    fn press_key(game: &mut Layered<RecordingRenderer, ScriptedInput>, keycode: Keycode) {
+        game.input.events.push_back(InputEvent::KeyDown(keycode));
+        game.handle_events();
+    }
+
+    #[test]
+    fn layered_screen_layers_and_ghosts() {
+        let mut model = LayeredModel::with_seed(2);
+        model.start((3, 3, 4), 4).unwrap();
+        let mut game = create_game(model);
+        let audio = RecordingAudio::default();
+        let sounds = audio.sounds.clone();
+        game.set_audio(Box::new(audio), false);
+        // the played layer is in the middle of the window
+        assert_eq!(game.camera.cell_size(), CELL);
+        assert_eq!(game.camera.cell_frame(0, 0).x(), (CELL * 5) as i32);
+
+        game.draw().unwrap();
+        assert_eq!(sprite_at(&game, 1, 1), Some(Sprite::Unknown));
+        // nothing above the first layer, the next one on the right
+        assert_eq!(sprite_at(&game, 1, -2), None);
+        assert_eq!(sprite_at(&game, 1, 4), None);
+        assert_eq!(sprite_at(&game, 1, 6), Some(Sprite::Unknown));
+        let ghost = game.color_manager.ghost();
+        let ghosts = game
+            .renderer
+            .commands
+            .iter()
+            .filter(|command| matches!(command, DrawCommand::FillRect(color, _) if *color == ghost))
+            .count();
+        assert_eq!(ghosts, 12);
+        assert_eq!(game.renderer.texts(), vec!["Layer 1/3  Mines: 4"]);
+
+        // the middle layer has both neighbours shown
+        press_key(&mut game, Keycode::PageDown);
+        let safe = (0..12)
+            .map(|index| (index / 4, index % 4))
+            .find(|&(row, col)| game.model.get_cell((1, row, col)).is_safe())
+            .unwrap();
+        click(&mut game, MouseButton::Left, safe.0, safe.1);
+        assert!(game.model.get_cell((1, safe.0, safe.1)).is_visible());
+        assert!(!sounds.borrow().is_empty());
+        game.draw().unwrap();
+        assert_ne!(
+            sprite_at(&game, safe.0, safe.1 as isize),
+            Some(Sprite::Unknown)
+        );
+        assert!(sprite_at(&game, 1, -2).is_some());
+        assert_eq!(game.renderer.texts()[0], "Layer 2/3  Mines: 4");
+
+        press_key(&mut game, Keycode::PageDown);
+        press_key(&mut game, Keycode::PageDown);
+        assert_eq!(game.layer, 2);
+        press_key(&mut game, Keycode::Q);
+        assert_eq!(game.layer, 1);
+
+        press_key(&mut game, Keycode::Escape);
+        assert!(!game.is_running);
+    }
+
+    #[test]
+    fn layered_screen_keyboard_play() {
+        let mut model = LayeredModel::with_seed(2);
+        model.start((3, 3, 4), 4).unwrap();
+        let mut game = create_game(model);
+        press_key(&mut game, Keycode::PageDown);
+        // the first key press only shows the cursor
+        press_key(&mut game, Keycode::Right);
+        assert!(game.is_cursor_visible);
+        assert_eq!(game.cursor, (0, 0));
+        press_key(&mut game, Keycode::Right);
+        press_key(&mut game, Keycode::Down);
+        assert_eq!(game.cursor, (1, 1));
+        press_key(&mut game, Keycode::F);
+        assert!(game.model.get_cell((1, 1, 1)).is_flagged());
+        press_key(&mut game, Keycode::F);
+
+        // the next covered cell skips flagged ones
+        game.cursor = (0, 0);
+        press_key(&mut game, Keycode::F);
+        press_key(&mut game, Keycode::Tab);
+        assert_eq!(game.cursor, (0, 1));
+        press_key(&mut game, Keycode::F);
+
+        let (row, col) = (0..12)
+            .map(|index| (index / 4, index % 4))
+            .find(|&(row, col)| game.model.get_cell((1, row, col)).is_safe())
+            .unwrap();
+        game.cursor = (row, col);
+        press_key(&mut game, Keycode::Return);
+        assert!(game.model.get_cell((1, row, col)).is_visible());
+        game.draw().unwrap();
+        let cursor = game.color_manager.cursor();
+        assert!(game
+            .renderer
+            .commands
+            .iter()
+            .any(|command| matches!(command, DrawCommand::FillRect(color, _) if *color == cursor)));
+    }
+
+    #[test]
+    fn layered_screen_depressed_cells() {
+        let mut model = LayeredModel::with_seed(2);
+        model.start((3, 3, 4), 4).unwrap();
+        let mut game = create_game(model);
+        press_key(&mut game, Keycode::PageDown);
+        let point = game.camera.cell_frame(1, 1).center();
+        game.input.mouse = MouseSnapshot {
+            x: point.x(),
+            y: point.y(),
+            buttons: HashSet::from([MouseButton::Middle]),
+        };
+        game.handle_events();
+        game.draw().unwrap();
+        // a chord presses the cells around in the played layer and both adjacent ones
+        assert_eq!(sprite_at(&game, 1, 1), Some(Sprite::Empty));
+        assert_eq!(sprite_at(&game, 0, 2), Some(Sprite::Empty));
+        assert_eq!(sprite_at(&game, 1, 3), Some(Sprite::Unknown));
+        assert_eq!(sprite_at(&game, 1, -4), Some(Sprite::Empty));
+        assert_eq!(sprite_at(&game, 2, 7), Some(Sprite::Empty));
+        assert_eq!(sprite_at(&game, 1, -2), Some(Sprite::Unknown));
+        assert_eq!(sprite_at(&game, 1, 8), Some(Sprite::Unknown));
+
+        // chording a covered cell does nothing
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+        game.draw().unwrap();
+        assert_eq!(sprite_at(&game, 1, 1), Some(Sprite::Unknown));
+        assert!(!game.model.get_cell((1, 1, 1)).is_visible());
+    }
+
+    #[test]
+    fn layered_screen_drag_pans() {
+        let mut model = LayeredModel::with_seed(2);
+        model.start((3, 3, 4), 4).unwrap();
+        let mut game = Layered::new(
+            model,
+            RecordingRenderer::default(),
+            ScriptedInput::default(),
+            ColorManager::default(),
+            Camera::new(CELL, 128),
+            Size {
+                height: CELL * 3,
+                width: CELL * 6,
+            },
+        );
+        let before = game.camera.cell_at(100, 50);
+        game.input.mouse = MouseSnapshot {
+            x: 100,
+            y: 50,
+            buttons: HashSet::from([MouseButton::Left]),
+        };
+        game.handle_events();
+        game.input.mouse.x -= 2 * CELL as i32;
+        game.handle_events();
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+        let after = game.camera.cell_at(100, 50);
+        assert_ne!(after, before);
+        let (rows, cols) = (0..3, 0..4);
+        let is_opened = rows
+            .flat_map(|row| cols.clone().map(move |col| (0, row, col)))
+            .any(|cell| game.model.get_cell(cell).is_visible());
+        assert!(!is_opened);
+    }
+}
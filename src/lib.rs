@@ -0,0 +1,2 @@
+// Game rules without the SDL front end, shared by the game and the benchmarks
+pub mod game_model;
@@ -1,7 +1,10 @@
 extern crate sdl2;
 
-mod game_model;
-use game_model::GameModel;
+// the leading colons select the library crate over the `minesweeper` module
+use ::minesweeper::game_model;
+use game_model::endless::EndlessModel;
+use game_model::layered::LayeredModel;
+use game_model::{GameModel, Grid};
 
 mod resources;
 use resources::*;
@@ -12,23 +15,134 @@ use minesweeper::{Minesweeper, Size};
 mod config;
 use config::Configuration;
 
+mod animation;
+mod audio;
+mod bindings;
+mod bot;
+mod camera;
+use camera::{Camera, UnboundedCamera};
+
+mod endless;
+use endless::Endless;
+mod export;
+mod key_value;
+mod layered;
+use layered::Layered;
+mod puzzle;
+use puzzle::PuzzlePack;
+mod render;
+mod review;
+mod screen;
+mod solver;
+
+const CONFIG_FILE: &str = "minesweeper.cfg";
+// Cells visible in the initial window of the endless mode
+const ENDLESS_VIEW: (u32, u32) = (20, 30);
+
+enum Mode {
+    Play(GameModel),
+    // finished game opened for analysis
+    Review(GameModel),
+    Endless(EndlessModel),
+    // board of several layers
+    Layered(LayeredModel),
+    // hand-made boards solved one after another
+    Puzzles(PuzzlePack),
+}
+
 fn main() -> Result<(), String> {
-    // TODO: config should be loaded
-    let config = Configuration;
-    let mut game_model = GameModel::new();
-    game_model.start(config.row_count(), config.col_count(), config.mines_count());
+    let args = std::env::args().collect::<Vec<String>>();
+    match args.get(1).map(String::as_str) {
+        Some("bench") => return bot::run_tournament_cli(&args[2..]),
+        Some("render") => return export::run_render_cli(&args[2..]),
+        _ => {}
+    }
 
+    let config = Configuration::load(std::path::Path::new(CONFIG_FILE))?;
+    let mode = match args.get(1).map(String::as_str) {
+        Some("replay") => {
+            let path = args
+                .get(2)
+                .ok_or("Usage: minesweeper replay <replay file>")?;
+            let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+            Mode::Review(game_model::replay::parse(&text)?)
+        }
+        Some("endless") => {
+            let seed = match args.get(2) {
+                Some(seed) => seed.parse().map_err(|_| format!("Invalid seed '{seed}'"))?,
+                None => rand::random(),
+            };
+            println!("Endless seed: {seed}");
+            Mode::Endless(EndlessModel::new(seed, config.endless_density()))
+        }
+        Some("layered") => {
+            let mut model = LayeredModel::new();
+            model.set_first_click_safe(config.is_first_click_safe());
+            let size = (config.layers(), config.row_count(), config.col_count());
+            model.start(size, config.layered_mines())?;
+            Mode::Layered(model)
+        }
+        Some("puzzles") => {
+            // packs not found as given are looked up in the assets
+            let dir = std::path::PathBuf::from(args.get(2).map_or("intro", String::as_str));
+            let dir = if dir.is_dir() {
+                dir
+            } else {
+                asset_path("puzzles").join(dir)
+            };
+            let mut pack = PuzzlePack::load(&dir)?;
+            pack.skip_solved();
+            Mode::Puzzles(pack)
+        }
+        _ => {
+            let mut game_model = GameModel::new();
+            game_model.set_first_click_safe(config.is_first_click_safe());
+            game_model.set_grid(config.grid());
+            game_model.set_topology(config.topology());
+            game_model.set_max_cell_mines(config.cell_mines());
+            let (mut rows, mut cols) = (config.row_count(), config.col_count());
+            if let Some(path) = config.mask() {
+                let mask = load_mask(path)?;
+                (rows, cols) = mask.size();
+                game_model.set_mask(Some(mask));
+            }
+            game_model.start(rows, cols, config.mines_count())?;
+            Mode::Play(game_model)
+        }
+    };
+    run_game(&config, mode)
+}
+
+fn run_game(config: &Configuration, mode: Mode) -> Result<(), String> {
     // sdl setup
-    let cell_size = Size {
-        height: 64,
-        width: 64,
+    let sdl_context = sdl2::init()?;
+    let video_subsystem = sdl_context.video()?;
+    // initial window should fit the screen, cells are scaled down if needed
+    let ((rows, cols), is_hex) = match &mode {
+        Mode::Play(game_model) | Mode::Review(game_model) => {
+            let (rows, cols) = game_model.board_size();
+            ((rows as u32, cols as u32), game_model.grid() == Grid::Hex)
+        }
+        Mode::Endless(_) => (ENDLESS_VIEW, false),
+        // the adjacent layers at both sides, one column apart
+        Mode::Layered(model) => {
+            let (_, rows, cols) = model.size();
+            ((rows as u32, 3 * cols as u32 + 2), false)
+        }
+        Mode::Puzzles(pack) => {
+            let (rows, cols) = pack.max_board_size();
+            ((rows as u32, cols as u32), false)
+        }
     };
+    let bounds = video_subsystem.display_usable_bounds(0)?;
+    let cell_size = (bounds.width() * 9 / 10 / cols)
+        .min(bounds.height() * 9 / 10 / rows)
+        .clamp(config.min_cell_size(), config.cell_size());
+    // shifted rows of hex boards stick out by half a cell
     let window_size = Size {
-        height: cell_size.height * config.row_count() as u32,
-        width: cell_size.width * config.col_count() as u32,
+        height: cell_size * rows,
+        width: cell_size * cols + is_hex as u32 * cell_size / 2,
     };
-    let sdl_context = sdl2::init()?;
-    let video_subsystem = sdl_context.video()?;
     let window = video_subsystem
         .window(
             &config.window_title(),
@@ -36,6 +150,7 @@ fn main() -> Result<(), String> {
             window_size.height,
         )
         .position_centered()
+        .resizable()
         .build()
         .map_err(|e| e.to_string())?;
     let canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
@@ -48,20 +163,62 @@ fn main() -> Result<(), String> {
 
     let event_pump = sdl_context.event_pump()?;
 
-    let color_manager = ColorManager;
+    let theme = find_theme(config.theme());
+    let color_manager = theme.colors().clone();
 
     let texture_creator = canvas.texture_creator();
 
-    let mut minesweeper = Minesweeper::new(
-        game_model,
+    let renderer = render::SdlRenderer::new(
         canvas,
         texture_creator,
         texture_manager,
         font_manager,
+        theme,
+    );
+    let input = render::SdlInput::new(event_pump);
+
+    let audio = audio::open_audio(&sdl_context, config.volume());
+    let (game_model, is_review, puzzles) = match mode {
+        Mode::Play(game_model) => (game_model, false, None),
+        Mode::Review(game_model) => (game_model, true, None),
+        Mode::Puzzles(pack) => (pack.current().model(), false, Some(pack)),
+        Mode::Endless(model) => {
+            let camera =
+                UnboundedCamera::new(config.min_cell_size(), config.max_cell_size(), cell_size);
+            let mut endless =
+                Endless::new(model, renderer, input, color_manager, camera, window_size);
+            endless.set_bindings(config.bindings().clone());
+            endless.set_audio(audio, config.is_muted());
+            return endless.run();
+        }
+        Mode::Layered(model) => {
+            let camera = Camera::new(config.min_cell_size(), config.max_cell_size());
+            let mut layered =
+                Layered::new(model, renderer, input, color_manager, camera, window_size);
+            layered.set_bindings(config.bindings().clone());
+            layered.set_audio(audio, config.is_muted());
+            return layered.run();
+        }
+    };
+    let mut minesweeper = Minesweeper::new(
+        game_model,
+        renderer,
+        input,
         color_manager,
-        event_pump,
-        cell_size,
+        Camera::new(config.min_cell_size(), config.max_cell_size()),
         window_size,
     );
+    let themes = available_themes(&themes_dir());
+    minesweeper.set_themes(themes, config.theme());
+    minesweeper.set_animations(config.animations());
+    minesweeper.set_bindings(config.bindings().clone());
+    minesweeper.set_ghost_columns(config.ghost_columns());
+    minesweeper.set_audio(audio, config.is_muted());
+    if is_review {
+        minesweeper.toggle_review();
+    }
+    if let Some(pack) = puzzles {
+        minesweeper.set_puzzles(pack);
+    }
     minesweeper.run()
 }
@@ -1,207 +1,1459 @@
-use std::collections::HashSet;
-use std::time::{Duration, Instant};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
 use sdl2::rect::{Point, Rect};
-use sdl2::render::TextureCreator;
-use sdl2::render::WindowCanvas;
-use sdl2::video::WindowContext;
-use sdl2::EventPump;
 
+use crate::animation::{explosion_frames, Animator};
+use crate::audio::{AudioSink, NoAudio, Sound};
+use crate::bindings::{KeyAction, KeyBindings};
+use crate::camera::Camera;
 use crate::game_model::*;
-use crate::resources::{ColorManager, FontManager, FontProvider, TextureManager, TextureProvider};
+use crate::puzzle::PuzzlePack;
+use crate::render::{number_color, InputEvent, InputSource, Renderer, Sprite};
+use crate::resources::{ColorManager, Theme};
+use crate::review::Review;
+use crate::screen::{self, *};
 
 pub struct Size {
     pub height: u32,
     pub width: u32,
 }
 
-pub struct Minesweeper<'a> {
+pub struct Minesweeper<R: Renderer, I: InputSource> {
     model: GameModel,
-    canvas: WindowCanvas,
-    texture_creator: TextureCreator<WindowContext>,
-    texture_manager: TextureManager<'a, WindowContext>,
-    font_manager: FontManager<'a>,
+    renderer: R,
+    input: I,
     color_manager: ColorManager,
-    event_pump: EventPump,
-    target_fps: u64,
     is_running: bool,
-    cell_size: Size,
+    camera: Camera,
     window_size: Size,
-    prev_mouse_buttons: HashSet<MouseButton>,
+    pointer: Pointer<(usize, usize)>,
+    screenshot_path: Option<PathBuf>,
+    themes: Vec<PathBuf>,
+    theme_index: usize,
+    audio: Box<dyn AudioSink>,
+    is_muted: bool,
+    animator: Animator,
+    review: Option<Review>,
+    bindings: KeyBindings,
+    // keyboard selected cell, shown after it's moved by keys
+    cursor: (usize, usize),
+    is_cursor_visible: bool,
+    // boards come from the pack instead of being generated
+    puzzles: Option<PuzzlePack>,
+    // "You win" and "Game Over" over the finished board
+    is_result_shown: bool,
+    // the minimap image is built again before it's drawn next time
+    is_minimap_stale: bool,
 }
 
-impl<'a> Minesweeper<'a> {
-    #[allow(clippy::too_many_arguments)]
+impl<R: Renderer, I: InputSource> Minesweeper<R, I> {
     pub fn new(
         model: GameModel,
-        canvas: WindowCanvas,
-        texture_creator: TextureCreator<WindowContext>,
-        texture_manager: TextureManager<'a, WindowContext>,
-        font_manager: FontManager<'a>,
+        renderer: R,
+        input: I,
         color_manager: ColorManager,
-        event_pump: EventPump,
-        cell_size: Size,
+        camera: Camera,
         window_size: Size,
     ) -> Self {
-        Self {
+        let mut minesweeper = Self {
             model,
-            canvas,
-            texture_creator,
-            texture_manager,
-            font_manager,
+            renderer,
+            input,
             color_manager,
-            event_pump,
-            target_fps: 24,
             is_running: false,
-            cell_size,
+            camera,
             window_size,
-            prev_mouse_buttons: HashSet::new(),
+            pointer: Pointer::new(),
+            screenshot_path: None,
+            themes: Vec::new(),
+            theme_index: 0,
+            audio: Box::new(NoAudio),
+            is_muted: false,
+            animator: Animator::new(false),
+            review: None,
+            bindings: KeyBindings::default(),
+            cursor: (0, 0),
+            is_cursor_visible: false,
+            puzzles: None,
+            is_result_shown: true,
+            is_minimap_stale: true,
+        };
+        minesweeper
+            .camera
+            .set_hex(minesweeper.model.grid() == Grid::Hex);
+        minesweeper.update_layout();
+        minesweeper
+    }
+
+    fn update_layout(&mut self) {
+        let viewport = (self.window_size.width, self.window_size.height);
+        self.camera.fit(viewport, self.model.board_size());
+    }
+
+    // Theme directories to cycle through, `current` is the name of the theme in use
+    pub fn set_themes(&mut self, themes: Vec<PathBuf>, current: &str) {
+        self.theme_index = themes
+            .iter()
+            .position(|dir| dir.file_name().is_some_and(|name| name == current))
+            .unwrap_or(themes.len().saturating_sub(1));
+        self.themes = themes;
+    }
+
+    pub fn set_audio(&mut self, audio: Box<dyn AudioSink>, is_muted: bool) {
+        self.audio = audio;
+        self.is_muted = is_muted;
+    }
+
+    pub fn set_animations(&mut self, is_enabled: bool) {
+        self.animator = Animator::new(is_enabled);
+    }
+
+    pub fn set_bindings(&mut self, bindings: KeyBindings) {
+        self.bindings = bindings;
+    }
+
+    pub fn set_result_shown(&mut self, is_shown: bool) {
+        self.is_result_shown = is_shown;
+    }
+
+    // Plays the puzzles of the pack starting with its current one
+    pub fn set_puzzles(&mut self, pack: PuzzlePack) {
+        let model = pack.current().model();
+        self.puzzles = Some(pack);
+        self.switch_model(model);
+    }
+
+    // Another board in place of the current game
+    fn switch_model(&mut self, model: GameModel) {
+        self.model = model;
+        self.review = None;
+        self.pointer.cancel();
+        self.cursor = (0, 0);
+        self.is_cursor_visible = false;
+        self.camera.set_hex(self.model.grid() == Grid::Hex);
+        self.update_layout();
+        self.handle_model_events(vec![GameEvent::Restarted]);
+    }
+
+    fn switch_puzzle(&mut self, is_next: bool) {
+        let Some(pack) = &mut self.puzzles else {
+            return;
+        };
+        let is_moved = if is_next {
+            pack.go_next()
+        } else {
+            pack.go_previous()
+        };
+        if is_moved {
+            let model = pack.current().model();
+            self.switch_model(model);
         }
     }
 
-    pub fn run(&mut self) -> Result<(), String> {
-        let target_frame_duration = Duration::from_millis(1000u64 / self.target_fps);
-        self.is_running = true;
-        loop {
-            let frame_start_time = Instant::now();
-            self.handle_events();
-            if !self.is_running {
-                break;
-            }
-            self.draw()?;
-            let elapsed_time = frame_start_time.elapsed();
-            let sleep_time = target_frame_duration.saturating_sub(elapsed_time);
-            if sleep_time.is_zero() {
-                println!("Frame elapsed time {elapsed_time:?}");
-                continue;
+    // Wrapped copies of the columns at the opposite edge drawn beside joined boards
+    pub fn set_ghost_columns(&mut self, cols: usize) {
+        if self.model.topology() == Topology::Toroidal {
+            self.camera.set_margin(cols);
+            self.update_layout();
+        }
+    }
+
+    fn play(&mut self, sound: Sound) {
+        if !self.is_muted {
+            self.audio.play(sound);
+        }
+    }
+
+    fn switch_theme(&mut self) {
+        if self.themes.is_empty() {
+            return;
+        }
+        let index = (self.theme_index + 1) % self.themes.len();
+        match Theme::load(&self.themes[index]) {
+            Ok(theme) => {
+                self.color_manager = theme.colors().clone();
+                self.is_minimap_stale = true;
+                self.renderer.set_theme(&theme);
+                println!("Theme: {}", theme.name());
             }
-            ::std::thread::sleep(sleep_time);
+            Err(e) => eprintln!("Theme is not loaded: {e}"),
         }
-        Ok(())
+        self.theme_index = index;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn renderer(&self) -> &R {
+        &self.renderer
+    }
+
+    // Screenshot is taken while drawing the next frame
+    pub fn request_screenshot(&mut self, path: PathBuf) {
+        self.screenshot_path = Some(path);
+    }
+
+    pub fn run(&mut self) -> Result<(), String> {
+        self.is_running = true;
+        screen::run(self)
     }
 
     pub fn handle_events(&mut self) {
-        for event in self.event_pump.poll_iter() {
+        for event in self.input.poll_events() {
             match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => self.is_running = false,
-                Event::KeyDown {
-                    keycode: Some(Keycode::Space),
-                    ..
-                } => self.model.restart(),
+                InputEvent::Quit => self.is_running = false,
+                InputEvent::KeyDown(keycode) => self.handle_key(keycode),
+                InputEvent::Resized(width, height) => {
+                    self.window_size = Size { height, width };
+                    self.update_layout();
+                }
+                InputEvent::MouseWheel(steps) => {
+                    let state = self.input.mouse_state();
+                    self.camera.zoom(steps, Point::new(state.x, state.y));
+                }
+            }
+        }
+
+        let state = self.input.mouse_state();
+        let cell = self.cell_at(Point::new(state.x, state.y));
+        let can_drag = !self.camera.is_board_visible();
+        let camera = &mut self.camera;
+        let press = self.pointer.update(state, cell, can_drag, |delta| {
+            camera.pan(delta.x(), delta.y())
+        });
+        if let Some(press) = press {
+            self.handle_click(&press);
+        }
+    }
+
+    // Board cell under the point, the minimap covers the board
+    fn cell_at(&self, point: Point) -> Option<(usize, usize)> {
+        let minimap = self.camera.minimap_frame();
+        if minimap.is_some_and(|minimap| minimap.contains_point(point)) {
+            return None;
+        }
+        self.camera.cell_at(point.x(), point.y())
+    }
+
+    fn hovered_cell(&self) -> Option<(usize, usize)> {
+        self.cell_at(self.pointer.position())
+    }
+
+    // Analysis of the finished game, the live game is not changed while reviewing
+    pub fn toggle_review(&mut self) {
+        self.is_minimap_stale = true;
+        if self.review.is_some() {
+            self.review = None;
+        } else if self.model.state() != State::InProgress {
+            self.animator.skip();
+            self.review = Some(Review::new(&self.model));
+        }
+    }
+
+    fn handle_key(&mut self, keycode: Keycode) {
+        let action = self.bindings.action(keycode);
+        if let Some(review) = &mut self.review {
+            self.is_minimap_stale = true;
+            match (keycode, action) {
+                (Keycode::Home, _) => review.go_to(0),
+                (Keycode::End, _) => review.go_to(review.moves_count()),
+                (_, Some(KeyAction::Left)) => review.go_back(),
+                (_, Some(KeyAction::Right)) => review.go_forward(),
                 _ => {}
             }
         }
+        let Some(action) = action else {
+            return;
+        };
+        match action {
+            KeyAction::Quit => self.is_running = false,
+            KeyAction::Restart => match &self.puzzles {
+                Some(pack) => self.switch_model(pack.current().model()),
+                None => {
+                    self.review = None;
+                    let events = self.model.restart();
+                    self.handle_model_events(events);
+                }
+            },
+            KeyAction::NextPuzzle => self.switch_puzzle(true),
+            KeyAction::PreviousPuzzle => self.switch_puzzle(false),
+            KeyAction::Screenshot => {
+                let path = format!("screenshot_{}.png", timestamp());
+                self.request_screenshot(PathBuf::from(path));
+            }
+            KeyAction::SaveReplay => self.save_replay(),
+            KeyAction::Review => self.toggle_review(),
+            KeyAction::Theme => self.switch_theme(),
+            KeyAction::Mute => self.is_muted = !self.is_muted,
+            // the board is read only while reviewing
+            _ if self.review.is_some() => {}
+            KeyAction::Up => self.move_cursor(-1, 0),
+            KeyAction::Down => self.move_cursor(1, 0),
+            KeyAction::Left => self.move_cursor(0, -1),
+            KeyAction::Right => self.move_cursor(0, 1),
+            KeyAction::NextCovered => self.move_cursor_to_covered(),
+            KeyAction::Open | KeyAction::Flag | KeyAction::Chord => {
+                self.is_cursor_visible = true;
+                self.act(action, self.cursor);
+            }
+            KeyAction::LayerUp | KeyAction::LayerDown => {}
+        }
+    }
+
+    fn save_replay(&self) {
+        let path = format!("replay_{}.txt", timestamp());
+        match fs::write(&path, replay::to_text(&self.model)) {
+            Ok(()) => println!("Replay saved to {path}"),
+            Err(e) => eprintln!("Replay is not saved: {e}"),
+        }
+    }
 
-        let state = self.event_pump.mouse_state();
-        let buttons = state
-            .pressed_mouse_buttons()
-            .collect::<HashSet<MouseButton>>();
-        if buttons.is_empty() && self.prev_mouse_buttons.len() == 1 {
-            let (x, y) = (state.x(), state.y());
-            if x >= 0 && y >= 0 {
-                let (x, y) = (x as u32, y as u32);
-                let row = (y / self.cell_size.width) as usize;
-                let col = (x / self.cell_size.height) as usize;
-                if self.prev_mouse_buttons.contains(&MouseButton::Left) {
-                    self.model.open_cell(row, col);
-                } else if self.prev_mouse_buttons.contains(&MouseButton::Right) {
-                    self.model.flag_cell(row, col);
+    // The first key press only shows the cursor, on joined boards it passes the edges
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        if self.is_cursor_visible {
+            let (row_count, col_count) = self.model.board_size();
+            let (row, col) = self.cursor;
+            self.cursor = self
+                .model
+                .topology()
+                .shift((row, col), (rows, cols), (row_count, col_count))
+                .unwrap_or((
+                    row.saturating_add_signed(rows).min(row_count - 1),
+                    col.saturating_add_signed(cols).min(col_count - 1),
+                ));
+        }
+        self.show_cursor();
+    }
+
+    // Next covered and not flagged cell in reading order, wrapping at the end of the board
+    fn move_cursor_to_covered(&mut self) {
+        let (rows, cols) = self.model.board_size();
+        let start = self.cursor.0 * cols + self.cursor.1;
+        let next = (1..=rows * cols)
+            .map(|offset| (start + offset) % (rows * cols))
+            .map(|index| (index / cols, index % cols))
+            .find(|&(row, col)| {
+                let cell = self.model.get_cell(row, col);
+                !cell.is_visible() && !cell.is_flagged() && !self.model.is_hole(row, col)
+            });
+        if let Some(cell) = next {
+            self.cursor = cell;
+        }
+        self.show_cursor();
+    }
+
+    fn show_cursor(&mut self) {
+        self.is_cursor_visible = true;
+        self.camera.ensure_visible(self.cursor.0, self.cursor.1);
+    }
+
+    fn handle_click(&mut self, press: &Press<(usize, usize)>) {
+        let position = self.pointer.position();
+        if let Some(minimap) = self.camera.minimap_frame() {
+            if minimap.contains_point(position) {
+                if let (true, Some((row, col))) = (
+                    press.buttons.contains(&MouseButton::Left),
+                    self.camera
+                        .minimap_cell_at(minimap, position.x(), position.y()),
+                ) {
+                    self.camera.center_on(row, col);
                 }
+                return;
             }
         }
-        self.prev_mouse_buttons = buttons;
+        if self.review.is_some() {
+            return;
+        }
+        let (Some(cell), Some(action)) = (press.cell, press.action()) else {
+            return;
+        };
+        // keyboard continues from the clicked cell
+        self.cursor = cell;
+        self.is_cursor_visible = false;
+        self.act(action, cell);
+    }
+
+    // Open, flag or chord of the cell by the mouse or the keyboard
+    fn act(&mut self, action: KeyAction, (row, col): (usize, usize)) {
+        let events = match action {
+            KeyAction::Open => self.model.open_cell(row, col),
+            KeyAction::Flag => self.model.flag_cell(row, col),
+            KeyAction::Chord => self.model.chord(row, col),
+            _ => return,
+        };
+        self.handle_model_events(events);
+    }
+
+    fn handle_model_events(&mut self, events: Vec<GameEvent>) {
+        self.is_minimap_stale |= !events.is_empty();
+        for event in events {
+            self.animator.handle_event(&event, &self.model);
+            let sound = match event {
+                GameEvent::CellsRevealed(cells) if cells.len() > 1 => Sound::Cascade,
+                GameEvent::CellsRevealed(_) => Sound::Reveal,
+                GameEvent::Flagged(..) => Sound::Flag,
+                GameEvent::Unflagged(..) => Sound::Unflag,
+                GameEvent::Exploded(..) => Sound::Explosion,
+                GameEvent::Won => {
+                    if let Some(pack) = &mut self.puzzles {
+                        pack.mark_solved();
+                    }
+                    Sound::Win
+                }
+                GameEvent::Restarted => continue,
+            };
+            self.play(sound);
+        }
     }
 
     pub fn draw(&mut self) -> Result<(), String> {
-        self.canvas.clear();
+        self.renderer.clear();
         match self.model.state() {
+            _ if self.review.is_some() => self.draw_review(),
             State::InProgress => self.draw_board(),
             State::Lose => self.draw_lose(),
             State::Win => self.draw_win(),
         }?;
-        self.canvas.present();
+        if let (Some(pack), None) = (&self.puzzles, &self.review) {
+            let status = pack.status();
+            self.show_status(&status)?;
+        }
+        if let Some(path) = self.screenshot_path.take() {
+            self.renderer.save_screenshot(&path)?;
+            println!("Screenshot saved to {}", path.display());
+        }
+        self.renderer.present();
         Ok(())
     }
 
+    // Reviewed position or the live game
+    fn displayed_model(&self) -> &GameModel {
+        self.review
+            .as_ref()
+            .map_or(&self.model, |review| review.position())
+    }
+
     fn draw_board(&mut self) -> Result<(), String> {
-        let is_lose = self.model.state() == State::Lose;
-        let (rows, cols) = self.model.board_size();
-        for col in 0..cols {
-            for row in 0..rows {
-                let cell = self.model.get_cell(row, col);
-                let texture = if !cell.is_safe() && is_lose {
-                    if self.model.is_last_step(row, col) {
-                        self.texture_manager.img_bomb_exploded()
-                    } else {
-                        self.texture_manager.img_bomb()
+        let is_lose = self.displayed_model().state() == State::Lose;
+        let pressed = self.pressed_cells();
+        let (rows, cols) = self.camera.visible_cells();
+        for col in cols {
+            for row in rows.clone() {
+                // holes are left as background
+                if self.model.is_hole(row, col) {
+                    continue;
+                }
+                let sprite = if pressed.contains(&(row, col)) {
+                    Sprite::Empty
+                } else {
+                    self.cell_sprite(row, col, is_lose)
+                };
+                let frame = self.camera.cell_frame(row, col);
+                self.renderer.draw_sprite(sprite, frame)?;
+                self.draw_cell_count(sprite, (row, col), frame)?;
+            }
+        }
+        self.draw_ghost_columns(is_lose)?;
+        if let Some((row, col)) = self.highlighted_cell() {
+            let frame = self.camera.cell_frame(row, col);
+            self.renderer.fill_rect(self.color_manager.hover(), frame)?;
+        }
+        if self.model.grid() == Grid::Hex {
+            let (rows, cols) = self.camera.visible_cells();
+            for row in rows {
+                for col in cols.clone() {
+                    if !self.model.is_hole(row, col) {
+                        let frame = self.camera.cell_frame(row, col);
+                        self.renderer
+                            .draw_hex_mask(self.color_manager.hex_gap(), frame)?;
                     }
-                } else if cell.is_flagged() {
-                    self.texture_manager.img_flag()
+                }
+            }
+        }
+        if let Some(((row, col), progress)) = self.animator.explosion() {
+            let frame = self.camera.cell_frame(row, col);
+            let (color, flash, sprite) =
+                explosion_frames(frame, progress, self.color_manager.explosion());
+            self.renderer.fill_rect(color, flash)?;
+            self.renderer.draw_sprite(Sprite::BombExploded, sprite)?;
+        }
+        if self.is_cursor_visible && self.review.is_none() {
+            let frame = self.camera.cell_frame(self.cursor.0, self.cursor.1);
+            draw_frame(&mut self.renderer, self.color_manager.cursor(), frame)?;
+        }
+        self.draw_minimap()
+    }
+
+    // Margin columns repeat the board from the opposite edge, tinted
+    fn draw_ghost_columns(&mut self, is_lose: bool) -> Result<(), String> {
+        let (rows, _) = self.camera.visible_cells();
+        let cols = self.model.board_size().1 as isize;
+        for col in self.camera.visible_margin_columns() {
+            let source = col.rem_euclid(cols) as usize;
+            for row in rows.clone() {
+                if self.model.is_hole(row, source) {
+                    continue;
+                }
+                let frame = self.camera.margin_frame(row, col);
+                let sprite = self.cell_sprite(row, source, is_lose);
+                self.renderer.draw_sprite(sprite, frame)?;
+                self.draw_cell_count(sprite, (row, source), frame)?;
+                self.renderer.fill_rect(self.color_manager.ghost(), frame)?;
+                if self.model.grid() == Grid::Hex {
+                    self.renderer
+                        .draw_hex_mask(self.color_manager.hex_gap(), frame)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Flags and mines of a cell holding several get their number in the corner
+    fn draw_cell_count(
+        &mut self,
+        sprite: Sprite,
+        (row, col): (usize, usize),
+        frame: Rect,
+    ) -> Result<(), String> {
+        let cell = self.displayed_model().get_cell(row, col);
+        let count = match sprite {
+            Sprite::Flag | Sprite::WrongFlag => cell.flags(),
+            Sprite::Bomb | Sprite::BombExploded => cell.mines(),
+            _ => 0,
+        };
+        if count < 2 {
+            return Ok(());
+        }
+        let (width, height) = (frame.width() / 3, frame.height() / 2);
+        let corner = Rect::new(
+            frame.right() - width as i32,
+            frame.bottom() - height as i32,
+            width,
+            height,
+        );
+        self.renderer
+            .draw_text(&count.to_string(), number_color(count), corner)
+    }
+
+    // Mouse feedback is given only to the live game in progress
+    fn is_board_interactive(&self) -> bool {
+        self.review.is_none()
+            && self.model.state() == State::InProgress
+            && !self.pointer.is_dragging()
+    }
+
+    // Covered cell under the mouse while no button is held
+    fn highlighted_cell(&self) -> Option<(usize, usize)> {
+        if self.pointer.is_pressed() || !self.is_board_interactive() {
+            return None;
+        }
+        self.hovered_cell().filter(|&(row, col)| {
+            !self.model.get_cell(row, col).is_visible() && !self.model.is_hole(row, col)
+        })
+    }
+
+    // Covered cells under the held buttons
+    fn pressed_cells(&self) -> Vec<(usize, usize)> {
+        if !self.is_board_interactive() {
+            return Vec::new();
+        }
+        let mut cells = self
+            .pointer
+            .depressed_cells(self.hovered_cell(), |(row, col)| {
+                self.model.neighbours(row, col)
+            });
+        cells.retain(|&(row, col)| {
+            let cell = self.model.get_cell(row, col);
+            !cell.is_visible() && !cell.is_flagged() && !self.model.is_hole(row, col)
+        });
+        cells
+    }
+
+    // Mines stay hidden until the loss animation reaches them, opened cells until the reveal
+    // animation does
+    fn cell_sprite(&self, row: usize, col: usize, is_lose: bool) -> Sprite {
+        let model = self.displayed_model();
+        let cell = model.get_cell(row, col);
+        let is_lose = is_lose && !self.animator.is_mine_hidden(row, col);
+        let is_exploded = is_lose && !cell.is_safe() && model.is_last_step(row, col);
+        match cell_sprite(cell, is_lose, is_exploded) {
+            Sprite::Empty | Sprite::Number(_) if self.animator.is_covered(row, col) => {
+                Sprite::Unknown
+            }
+            sprite => sprite,
+        }
+    }
+
+    fn draw_minimap(&mut self) -> Result<(), String> {
+        let Some(frame) = self.camera.minimap_frame() else {
+            return Ok(());
+        };
+        if self.is_minimap_stale {
+            let pixels = self.minimap_pixels();
+            self.renderer
+                .update_minimap(self.model.board_size(), &pixels)?;
+            self.is_minimap_stale = false;
+        }
+        self.renderer.draw_minimap(frame)?;
+        let viewport = self.camera.minimap_viewport(frame);
+        draw_outline(
+            &mut self.renderer,
+            self.color_manager.minimap_viewport(),
+            viewport,
+        )
+    }
+
+    // One pixel per cell, holes are transparent
+    fn minimap_pixels(&self) -> Vec<Color> {
+        let model = self.displayed_model();
+        let (rows, cols) = model.board_size();
+        let mut pixels = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                let cell = model.get_cell(row, col);
+                let color = if model.is_hole(row, col) {
+                    Color::RGBA(0, 0, 0, 0)
                 } else if cell.is_visible() {
-                    match cell.mines_count() {
-                        0 => self.texture_manager.img_empty(),
-                        count => self.texture_manager.img_number(count),
-                    }
+                    self.color_manager.minimap_opened()
+                } else if cell.is_flagged() {
+                    self.color_manager.minimap_flag()
                 } else {
-                    self.texture_manager.img_unknown()
-                }?;
-                let cell_width = self.cell_size.width;
-                let cell_height = self.cell_size.height;
-                let src = Rect::new(0, 0, cell_width, cell_height);
-                let w = cell_width as i32;
-                let h = cell_height as i32;
-                let center = Point::new(w / 2, h / 2);
-                let dest = Rect::new(col as i32 * w, row as i32 * w, cell_width, cell_height);
-                self.canvas
-                    .copy_ex(&texture, src, dest, 0.0, center, false, false)?;
+                    self.color_manager.minimap_covered()
+                };
+                pixels.push(color);
             }
         }
-        Ok(())
+        pixels
+    }
+
+    // Board of the reviewed step with logically safe cells and the next move marked
+    fn draw_review(&mut self) -> Result<(), String> {
+        self.draw_board()?;
+        let Some(review) = &self.review else {
+            return Ok(());
+        };
+        let (rows, cols) = self.camera.visible_cells();
+        let mut safe_cells = Vec::new();
+        for row in rows {
+            for col in cols.clone() {
+                if review.analysis().probability(row, col) == Some(0.0) {
+                    safe_cells.push(self.camera.cell_frame(row, col));
+                }
+            }
+        }
+        let next_move = review.next_move().map(|action| {
+            let (row, col) = action.cell();
+            self.camera.cell_frame(row, col)
+        });
+        let summary = review.summary();
+
+        for frame in safe_cells {
+            self.renderer
+                .fill_rect(self.color_manager.analysis_safe(), frame)?;
+        }
+        if let Some(frame) = next_move {
+            draw_frame(
+                &mut self.renderer,
+                self.color_manager.analysis_move(),
+                frame,
+            )?;
+        }
+        self.show_status(&summary)
+    }
+
+    fn show_status(&mut self, text: &str) -> Result<(), String> {
+        show_status(
+            &mut self.renderer,
+            &self.color_manager,
+            &self.window_size,
+            text,
+        )
     }
 
     fn draw_win(&mut self) -> Result<(), String> {
         self.draw_board()?;
-        self.show_message("You win")
+        if self.is_result_shown && self.animator.is_finished() {
+            self.show_message("You win")?;
+        }
+        let window = (self.window_size.width, self.window_size.height);
+        for (color, rect) in self.animator.confetti(window) {
+            self.renderer.fill_rect(color, rect)?;
+        }
+        Ok(())
     }
 
+    // Result is shown after the mines are revealed
     fn draw_lose(&mut self) -> Result<(), String> {
         self.draw_board()?;
-        self.show_message("Game Over")
+        if self.is_result_shown && self.animator.is_finished() {
+            self.show_message("Game Over")?;
+        }
+        Ok(())
     }
 
     fn show_message(&mut self, text: &str) -> Result<(), String> {
-        let (w, h) = (self.window_size.width, self.window_size.height);
-
-        self.canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
-        self.canvas.set_draw_color(self.color_manager.fade());
-        self.canvas.fill_rect(Rect::new(0, 0, w, h))?;
-
-        self.canvas.set_blend_mode(sdl2::render::BlendMode::None);
-
-        let font = self.font_manager.font_header()?;
-        let surface = font
-            .render(text)
-            .blended(self.color_manager.title_message())
-            .map_err(|e| e.to_string())?;
-        let texture = self
-            .texture_creator
-            .create_texture_from_surface(&surface)
-            .map_err(|e| e.to_string())?;
-
-        let size = Size {
-            height: 150,
-            width: 650,
+        show_message(
+            &mut self.renderer,
+            &self.color_manager,
+            &self.window_size,
+            text,
+        )
+    }
+}
+
+impl<R: Renderer, I: InputSource> Screen for Minesweeper<R, I> {
+    fn handle_events(&mut self) {
+        self.handle_events();
+    }
+
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    fn draw(&mut self) -> Result<(), String> {
+        self.draw()
+    }
+
+    fn advance(&mut self, elapsed: Duration) {
+        self.animator.advance(elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::RecordingAudio;
+    use crate::render::{DrawCommand, RecordingRenderer, ScriptedInput};
+    use std::collections::HashSet;
+
+    const CELL: u32 = 64;
+
+    fn create_game(model: GameModel) -> Minesweeper<RecordingRenderer, ScriptedInput> {
+        let (rows, cols) = model.board_size();
+        Minesweeper::new(
+            model,
+            RecordingRenderer::default(),
+            ScriptedInput::default(),
+            ColorManager::default(),
+            Camera::new(16, 128),
+            Size {
+                height: CELL * rows as u32,
+                width: CELL * cols as u32,
+            },
+        )
+    }
+
+    fn find_cell(model: &GameModel, predicate: impl Fn(&Cell) -> bool) -> (usize, usize) {
+        let (rows, cols) = model.board_size();
+        (0..rows * cols)
+            .map(|i| (i / cols, i % cols))
+            .find(|(r, c)| predicate(&model.get_cell(*r, *c)))
+            .unwrap()
+    }
+
+    fn sprite_at_cell(
+        game: &Minesweeper<RecordingRenderer, ScriptedInput>,
+        row: usize,
+        col: usize,
+    ) -> Option<Sprite> {
+        let half = CELL as i32 / 2;
+        game.renderer.sprite_at(
+            col as i32 * CELL as i32 + half,
+            row as i32 * CELL as i32 + half,
+        )
+    }
+
+    fn click(
+        game: &mut Minesweeper<RecordingRenderer, ScriptedInput>,
+        button: MouseButton,
+        row: usize,
+        col: usize,
+    ) {
+        game.input.mouse.x = col as i32 * CELL as i32 + 1;
+        game.input.mouse.y = row as i32 * CELL as i32 + 1;
+        game.input.mouse.buttons = HashSet::from([button]);
+        game.handle_events();
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+    }
+
+    #[test]
+    fn draw_initial_board_covered() {
+        let mut model = GameModel::with_seed(1);
+        model.start(4, 5, 3).unwrap();
+        let mut game = create_game(model);
+        game.draw().unwrap();
+        for row in 0..4 {
+            for col in 0..5 {
+                assert_eq!(sprite_at_cell(&game, row, col), Some(Sprite::Unknown));
+            }
+        }
+    }
+
+    #[test]
+    fn draw_opened_and_flagged_cells() {
+        let mut model = GameModel::with_seed(2);
+        model.start(8, 8, 10).unwrap();
+        let number = find_cell(&model, |cell| cell.is_safe() && cell.mines_count() > 0);
+        let mine = find_cell(&model, |cell| !cell.is_safe());
+        let count = model.get_cell(number.0, number.1).mines_count();
+        let mut game = create_game(model);
+        click(&mut game, MouseButton::Left, number.0, number.1);
+        click(&mut game, MouseButton::Right, mine.0, mine.1);
+        game.draw().unwrap();
+        assert_eq!(
+            sprite_at_cell(&game, number.0, number.1),
+            Some(Sprite::Number(count))
+        );
+        assert_eq!(sprite_at_cell(&game, mine.0, mine.1), Some(Sprite::Flag));
+    }
+
+    #[test]
+    fn draw_lose_shows_mines_and_message() {
+        let mut model = GameModel::with_seed(3);
+        model.start(8, 12, 10).unwrap();
+        let (rows, cols) = model.board_size();
+        let mines = (0..rows * cols)
+            .map(|i| (i / cols, i % cols))
+            .filter(|(r, c)| !model.get_cell(*r, *c).is_safe())
+            .collect::<Vec<_>>();
+        let mut game = create_game(model);
+        click(&mut game, MouseButton::Left, mines[0].0, mines[0].1);
+        game.draw().unwrap();
+        assert_eq!(
+            sprite_at_cell(&game, mines[0].0, mines[0].1),
+            Some(Sprite::BombExploded)
+        );
+        for (row, col) in &mines[1..] {
+            assert_eq!(sprite_at_cell(&game, *row, *col), Some(Sprite::Bomb));
+        }
+        assert_eq!(game.renderer.texts(), vec!["Game Over"]);
+    }
+
+    fn press(
+        game: &mut Minesweeper<RecordingRenderer, ScriptedInput>,
+        button: MouseButton,
+        x: i32,
+        y: i32,
+    ) {
+        game.input.mouse.x = x;
+        game.input.mouse.y = y;
+        game.input.mouse.buttons = HashSet::from([button]);
+        game.handle_events();
+    }
+
+    fn release(game: &mut Minesweeper<RecordingRenderer, ScriptedInput>) {
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+    }
+
+    fn sprites_count(game: &Minesweeper<RecordingRenderer, ScriptedInput>) -> usize {
+        game.renderer
+            .commands
+            .iter()
+            .filter(|command| matches!(command, DrawCommand::Sprite(..)))
+            .count()
+    }
+
+    #[test]
+    fn resize_scales_cells() {
+        let model =
+            crate::game_model::layout::parse("........\n........\n........\n.......*").unwrap();
+        let mut game = create_game(model);
+        // 4x8 board in 400x400 window: 50px cells, board centered vertically
+        game.input.events.push_back(InputEvent::Resized(400, 400));
+        press(&mut game, MouseButton::Left, 125, 99);
+        release(&mut game);
+        assert_eq!(game.camera.cell_size(), 50);
+        assert!(!game.model.get_cell(0, 2).is_visible());
+
+        press(&mut game, MouseButton::Left, 125, 110);
+        release(&mut game);
+        assert!(game.model.get_cell(0, 2).is_visible());
+    }
+
+    #[test]
+    fn resize_keeps_minimal_cell_size() {
+        let mut model = GameModel::new();
+        model.start(16, 30, 99).unwrap();
+        let mut game = create_game(model);
+        game.input.events.push_back(InputEvent::Resized(200, 100));
+        game.handle_events();
+        assert_eq!(game.camera.cell_size(), 16);
+    }
+
+    #[test]
+    fn huge_board_draws_visible_cells_and_minimap() {
+        let mut model = GameModel::with_seed(5);
+        model.start(200, 200, 4000).unwrap();
+        let mut game = create_game(model);
+        game.input.events.push_back(InputEvent::Resized(800, 600));
+        game.handle_events();
+        game.draw().unwrap();
+        assert_eq!(sprites_count(&game), 38 * 50);
+        let minimap = game.camera.minimap_frame().unwrap();
+        assert!(game
+            .renderer
+            .commands
+            .contains(&DrawCommand::Minimap(minimap)));
+    }
+
+    #[test]
+    fn minimap_is_updated_on_board_change() {
+        let model = crate::game_model::layout::parse(&"-.*.\n".repeat(100)).unwrap();
+        let mut game = create_game(model);
+        game.input.events.push_back(InputEvent::Resized(800, 600));
+        game.handle_events();
+        game.draw().unwrap();
+        game.draw().unwrap();
+        assert_eq!(game.renderer.minimap_updates, 1);
+        let colors = ColorManager::default();
+        assert_eq!(game.renderer.minimap[0], Color::RGBA(0, 0, 0, 0));
+        assert_eq!(game.renderer.minimap[1], colors.minimap_covered());
+
+        game.cursor = (0, 1);
+        game.input
+            .events
+            .push_back(InputEvent::KeyDown(Keycode::Return));
+        game.handle_events();
+        game.draw().unwrap();
+        assert_eq!(game.renderer.minimap_updates, 2);
+        assert_eq!(game.renderer.minimap[1], colors.minimap_opened());
+    }
+
+    #[test]
+    fn drag_pans_without_click() {
+        let mut model = GameModel::with_seed(6);
+        model.start(200, 200, 0).unwrap();
+        let mut game = create_game(model);
+        game.input.events.push_back(InputEvent::Resized(800, 600));
+        press(&mut game, MouseButton::Left, 400, 300);
+        press(&mut game, MouseButton::Left, 380, 300);
+        press(&mut game, MouseButton::Left, 300, 200);
+        release(&mut game);
+        assert_eq!(game.camera.cell_at(0, 0), Some((6, 6)));
+        assert!(!game.model.get_cell(0, 0).is_visible());
+    }
+
+    #[test]
+    fn wheel_and_keys_move_camera() {
+        let mut model = GameModel::with_seed(7);
+        model.start(200, 200, 0).unwrap();
+        let mut game = create_game(model);
+        game.input.events.push_back(InputEvent::Resized(800, 600));
+        game.input.events.push_back(InputEvent::MouseWheel(1));
+        game.handle_events();
+        assert_eq!(game.camera.cell_size(), 20);
+        // the camera follows the cursor, the first key press only shows it
+        for _ in 0..50 {
+            game.input
+                .events
+                .push_back(InputEvent::KeyDown(Keycode::Down));
+        }
+        game.handle_events();
+        assert_eq!(game.cursor, (49, 0));
+        assert_eq!(game.camera.cell_at(0, 0), Some((20, 0)));
+    }
+
+    #[test]
+    fn minimap_click_centers_camera() {
+        let mut model = GameModel::with_seed(8);
+        model.start(200, 200, 0).unwrap();
+        let mut game = create_game(model);
+        game.input.events.push_back(InputEvent::Resized(800, 600));
+        game.handle_events();
+        let minimap = game.camera.minimap_frame().unwrap();
+        let center = minimap.center();
+        press(&mut game, MouseButton::Left, center.x(), center.y());
+        release(&mut game);
+        assert_eq!(game.camera.cell_at(400, 300), Some((100, 100)));
+        assert!(!game.model.get_cell(100, 100).is_visible());
+    }
+
+    #[test]
+    fn draw_scales_cells_to_window() {
+        let mut model = GameModel::with_seed(3);
+        model.start(8, 12, 10).unwrap();
+        let mut game = create_game(model);
+        // 50px cells limited by the width, the board is centered vertically
+        game.input.events.push_back(InputEvent::Resized(600, 500));
+        game.handle_events();
+        game.draw().unwrap();
+        let frames = game
+            .renderer
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Sprite(_, frame) => Some(*frame),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(frames.len(), 96);
+        assert_eq!(frames[0], Rect::new(0, 50, 50, 50));
+        assert!(frames.contains(&Rect::new(550, 400, 50, 50)));
+    }
+
+    #[test]
+    fn message_fits_small_window() {
+        let model = crate::game_model::layout::parse("X.\n..").unwrap();
+        let mut game = create_game(model);
+        game.draw().unwrap();
+        let frame = game
+            .renderer
+            .commands
+            .iter()
+            .find_map(|command| match command {
+                DrawCommand::Text(_, frame) => Some(*frame),
+                _ => None,
+            })
+            .unwrap();
+        assert!(frame.x() >= 0 && frame.right() <= 128);
+        assert!(frame.y() >= 0 && frame.bottom() <= 128);
+    }
+
+    #[test]
+    fn screenshot_taken_before_present() {
+        let mut model = GameModel::new();
+        model.start(4, 4, 1).unwrap();
+        let mut game = create_game(model);
+        game.input
+            .events
+            .push_back(InputEvent::KeyDown(Keycode::F12));
+        game.handle_events();
+        game.draw().unwrap();
+        let commands = &game.renderer.commands;
+        assert!(matches!(
+            commands[commands.len() - 2],
+            DrawCommand::Screenshot(ref path) if path.to_string_lossy().starts_with("screenshot_")
+        ));
+        assert_eq!(commands.last(), Some(&DrawCommand::Present));
+
+        game.draw().unwrap();
+        assert!(!game
+            .renderer
+            .commands
+            .iter()
+            .any(|command| matches!(command, DrawCommand::Screenshot(_))));
+    }
+
+    #[test]
+    fn theme_hotkey_cycles_themes() {
+        let mut model = GameModel::new();
+        model.start(4, 4, 1).unwrap();
+        let mut game = create_game(model);
+        let themes = crate::resources::available_themes(&crate::resources::themes_dir());
+        game.set_themes(themes.clone(), "classic");
+        for _ in 0..themes.len() {
+            game.input.events.push_back(InputEvent::KeyDown(Keycode::T));
+            game.handle_events();
+            let name = game.renderer.theme_name.clone().unwrap();
+            assert_eq!(
+                name,
+                themes[game.theme_index]
+                    .file_name()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+            );
+        }
+        assert_eq!(game.renderer.theme_name.as_deref(), Some("classic"));
+    }
+
+    #[test]
+    fn actions_play_sounds() {
+        let mut model = GameModel::with_seed(3);
+        model.start(8, 8, 10).unwrap();
+        let number = find_cell(&model, |cell| cell.is_safe() && cell.mines_count() > 0);
+        let empty = find_cell(&model, |cell| cell.is_safe() && cell.mines_count() == 0);
+        let mine = find_cell(&model, |cell| !cell.is_safe());
+        let mut game = create_game(model);
+        let audio = RecordingAudio::default();
+        let sounds = audio.sounds.clone();
+        game.set_audio(Box::new(audio), false);
+
+        click(&mut game, MouseButton::Left, number.0, number.1);
+        click(&mut game, MouseButton::Left, number.0, number.1);
+        click(&mut game, MouseButton::Right, mine.0, mine.1);
+        click(&mut game, MouseButton::Right, mine.0, mine.1);
+        // muted flag
+        game.input.events.push_back(InputEvent::KeyDown(Keycode::M));
+        click(&mut game, MouseButton::Right, mine.0, mine.1);
+        game.input.events.push_back(InputEvent::KeyDown(Keycode::M));
+        click(&mut game, MouseButton::Right, mine.0, mine.1);
+        click(&mut game, MouseButton::Left, empty.0, empty.1);
+        click(&mut game, MouseButton::Left, mine.0, mine.1);
+        click(&mut game, MouseButton::Left, mine.0, mine.1);
+        assert_eq!(
+            *sounds.borrow(),
+            vec![
+                Sound::Reveal,
+                Sound::Flag,
+                Sound::Unflag,
+                Sound::Unflag,
+                Sound::Cascade,
+                Sound::Explosion
+            ]
+        );
+    }
+
+    #[test]
+    fn animated_lose_shows_message_after_mines() {
+        let mut model = GameModel::with_seed(3);
+        model.start(8, 8, 10).unwrap();
+        let mine = find_cell(&model, |cell| !cell.is_safe());
+        let mut game = create_game(model);
+        game.set_animations(true);
+        click(&mut game, MouseButton::Left, mine.0, mine.1);
+        game.draw().unwrap();
+        assert!(game.renderer.texts().is_empty());
+        assert_eq!(sprites_count(&game), 8 * 8 + 1);
+        assert_eq!(
+            game.renderer
+                .commands
+                .iter()
+                .filter(|command| matches!(command, DrawCommand::Sprite(Sprite::Bomb, _)))
+                .count(),
+            0
+        );
+
+        game.animator.advance(Duration::from_secs(3));
+        game.draw().unwrap();
+        assert_eq!(game.renderer.texts(), vec!["Game Over"]);
+        assert_eq!(sprites_count(&game), 8 * 8);
+    }
+
+    #[test]
+    fn draw_lose_marks_wrong_flags() {
+        let mut model = GameModel::with_seed(3);
+        model.start(8, 8, 10).unwrap();
+        let (rows, cols) = model.board_size();
+        let mines = (0..rows * cols)
+            .map(|i| (i / cols, i % cols))
+            .filter(|(r, c)| !model.get_cell(*r, *c).is_safe())
+            .collect::<Vec<_>>();
+        let safe = find_cell(&model, |cell| cell.is_safe() && !cell.is_visible());
+        let mut game = create_game(model);
+        click(&mut game, MouseButton::Right, mines[1].0, mines[1].1);
+        click(&mut game, MouseButton::Right, safe.0, safe.1);
+        click(&mut game, MouseButton::Left, mines[0].0, mines[0].1);
+        game.draw().unwrap();
+        assert_eq!(
+            sprite_at_cell(&game, mines[0].0, mines[0].1),
+            Some(Sprite::BombExploded)
+        );
+        assert_eq!(
+            sprite_at_cell(&game, mines[1].0, mines[1].1),
+            Some(Sprite::Flag)
+        );
+        assert_eq!(
+            sprite_at_cell(&game, safe.0, safe.1),
+            Some(Sprite::WrongFlag)
+        );
+        for (row, col) in &mines[2..] {
+            assert_eq!(sprite_at_cell(&game, *row, *col), Some(Sprite::Bomb));
+        }
+    }
+
+    #[test]
+    fn review_after_loss() {
+        let model = replay::parse(
+            "*..
+            ...
+            ..*
+            open 0 2
+            open 0 0",
+        )
+        .unwrap();
+        let mut game = create_game(model);
+        let key = |game: &mut Minesweeper<RecordingRenderer, ScriptedInput>, keycode| {
+            game.input.events.push_back(InputEvent::KeyDown(keycode));
+            game.handle_events();
+            game.draw().unwrap();
         };
-        let x = (w - size.width) / 2;
-        let y = (h - size.height) / 2;
-        let frame = Rect::new(x as i32, y as i32, size.width, size.height);
-        self.canvas.copy(&texture, None, Some(frame))
+        key(&mut game, Keycode::R);
+        let texts = game.renderer.texts();
+        assert_eq!(texts.len(), 1);
+        assert!(texts[0].starts_with("Move 2/2: open 0:0"));
+        // the fatal cell is not opened yet, the safe one is marked
+        assert_eq!(sprite_at_cell(&game, 0, 0), Some(Sprite::Unknown));
+        let safe = game.camera.cell_frame(2, 0);
+        assert!(game.renderer.commands.contains(&DrawCommand::FillRect(
+            game.color_manager.analysis_safe(),
+            safe
+        )));
+
+        key(&mut game, Keycode::Left);
+        assert!(game.renderer.texts()[0].starts_with("Move 1/2: open 0:2"));
+        assert_eq!(sprite_at_cell(&game, 0, 2), Some(Sprite::Unknown));
+        click(&mut game, MouseButton::Left, 0, 2);
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, 0, 2), Some(Sprite::Unknown));
+
+        key(&mut game, Keycode::R);
+        assert_eq!(game.renderer.texts(), vec!["Game Over"]);
+    }
+
+    #[test]
+    fn keyboard_play() {
+        let model = replay::parse(
+            "*..
+            ...
+            ..*",
+        )
+        .unwrap();
+        let mut game = create_game(model);
+        let keys = |game: &mut Minesweeper<RecordingRenderer, ScriptedInput>, keys: &[Keycode]| {
+            for keycode in keys {
+                game.input.events.push_back(InputEvent::KeyDown(*keycode));
+            }
+            game.handle_events();
+        };
+        keys(&mut game, &[Keycode::Right, Keycode::D, Keycode::L]);
+        assert_eq!(game.cursor, (0, 2));
+        keys(&mut game, &[Keycode::Return]);
+        assert!(game.model.get_cell(1, 1).is_visible());
+        keys(&mut game, &[Keycode::N]);
+        assert_eq!(game.cursor, (1, 0));
+        keys(&mut game, &[Keycode::K, Keycode::F]);
+        assert!(game.model.get_cell(0, 0).is_flagged());
+
+        // not enough flags around
+        keys(&mut game, &[Keycode::J, Keycode::L, Keycode::C]);
+        assert!(!game.model.get_cell(1, 0).is_visible());
+        game.draw().unwrap();
+        let cursor = Rect::new(CELL as i32, CELL as i32, CELL, 1);
+        assert!(game
+            .renderer
+            .commands
+            .contains(&DrawCommand::FillRect(game.color_manager.cursor(), cursor)));
+
+        keys(&mut game, &[Keycode::S, Keycode::Right, Keycode::F]);
+        keys(&mut game, &[Keycode::W, Keycode::H, Keycode::C]);
+        assert_eq!(game.model.state(), State::Win);
+
+        // clicks hide the cursor and move it to the clicked cell
+        keys(&mut game, &[Keycode::Space]);
+        click(&mut game, MouseButton::Right, 2, 0);
+        assert_eq!(game.cursor, (2, 0));
+        assert!(!game.is_cursor_visible);
+    }
+
+    #[test]
+    fn press_feedback_and_cancel() {
+        let model = replay::parse(
+            "*..*
+            ....
+            ....",
+        )
+        .unwrap();
+        let mut game = create_game(model);
+        let hover = |game: &Minesweeper<RecordingRenderer, ScriptedInput>, row, col| {
+            let color = game.color_manager.hover();
+            let frame = game.camera.cell_frame(row, col);
+            game.renderer
+                .commands
+                .contains(&DrawCommand::FillRect(color, frame))
+        };
+        game.input.mouse.x = CELL as i32 + 1;
+        game.handle_events();
+        game.draw().unwrap();
+        assert!(hover(&game, 0, 1));
+
+        press(&mut game, MouseButton::Left, CELL as i32 + 1, 1);
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, 0, 1), Some(Sprite::Empty));
+        assert!(!hover(&game, 0, 1));
+        // leaving the pressed cell raises it, releasing there does nothing
+        game.input.mouse.x = 2 * CELL as i32 + 1;
+        game.handle_events();
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, 0, 1), Some(Sprite::Unknown));
+        release(&mut game);
+        assert!(!game.model.get_cell(0, 1).is_visible());
+        assert!(!game.model.get_cell(0, 2).is_visible());
+
+        click(&mut game, MouseButton::Left, 1, 1);
+        click(&mut game, MouseButton::Right, 0, 0);
+        press(
+            &mut game,
+            MouseButton::Middle,
+            CELL as i32 + 1,
+            CELL as i32 + 1,
+        );
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, 0, 0), Some(Sprite::Flag));
+        assert_eq!(sprite_at_cell(&game, 1, 1), Some(Sprite::Number(1)));
+        for (row, col) in [(0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)] {
+            assert_eq!(sprite_at_cell(&game, row, col), Some(Sprite::Empty));
+        }
+        assert_eq!(sprite_at_cell(&game, 2, 3), Some(Sprite::Unknown));
+        release(&mut game);
+        assert_eq!(game.model.state(), State::Win);
+    }
+
+    #[test]
+    fn toroidal_ghost_columns_and_cursor() {
+        let model = crate::game_model::layout::parse(
+            "topology toroidal
+            ...o
+            *...
+            ....",
+        )
+        .unwrap();
+        let mut game = create_game(model);
+        game.set_ghost_columns(1);
+        game.draw().unwrap();
+        let sprite_at = |game: &Minesweeper<RecordingRenderer, ScriptedInput>, row, col| {
+            let center = game.camera.margin_frame(row, col).center();
+            game.renderer.sprite_at(center.x(), center.y())
+        };
+        // the opened corner counts the mine across both edges
+        assert_eq!(sprite_at(&game, 0, 3), Some(Sprite::Number(1)));
+        assert_eq!(sprite_at(&game, 0, -1), Some(Sprite::Number(1)));
+        assert_eq!(sprite_at(&game, 0, 4), Some(Sprite::Unknown));
+        assert!(game.camera.cell_frame(0, 0).x() > 0);
+
+        for keycode in [Keycode::Left, Keycode::Left, Keycode::Up] {
+            game.input.events.push_back(InputEvent::KeyDown(keycode));
+        }
+        game.handle_events();
+        assert_eq!(game.cursor, (2, 3));
+    }
+
+    #[test]
+    fn hex_board_click_and_gaps() {
+        let model = crate::game_model::layout::parse(
+            "grid hex
+            *...
+            ....
+            ..*.",
+        )
+        .unwrap();
+        let mut game = create_game(model);
+        // shifted rows take half a cell more
+        let size = game.camera.cell_size() as i32;
+        assert_eq!(size, CELL as i32 * 8 / 9);
+        let frame = game.camera.cell_frame(1, 3);
+        assert_eq!(frame.x(), game.camera.cell_frame(0, 3).x() + size / 2);
+        game.input.mouse.x = frame.right() - 2;
+        game.input.mouse.y = frame.center().y();
+        game.input.mouse.buttons = HashSet::from([MouseButton::Left]);
+        game.handle_events();
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+        assert!(game.model.get_cell(1, 3).is_visible());
+        // the cell touches the mine below its right edge only
+        assert_eq!(game.model.get_cell(1, 3).mines_count(), 0);
+
+        game.draw().unwrap();
+        let masks = game
+            .renderer
+            .commands
+            .iter()
+            .filter(|command| matches!(command, DrawCommand::HexMask(..)))
+            .count();
+        assert_eq!(masks, 12);
+
+        // the corner gaps belong to no cell
+        let frame = game.camera.cell_frame(0, 0);
+        game.input.mouse.x = frame.x() + 1;
+        game.input.mouse.y = frame.y() + 1;
+        game.input.mouse.buttons = HashSet::from([MouseButton::Left]);
+        game.handle_events();
+        game.input.mouse.buttons.clear();
+        game.handle_events();
+        assert_eq!(game.model.state(), State::InProgress);
+    }
+
+    #[test]
+    fn multi_mine_flag_counts() {
+        let model = crate::game_model::layout::parse(
+            "cell_mines 3
+            3..
+            ..*",
+        )
+        .unwrap();
+        let mut game = create_game(model);
+        click(&mut game, MouseButton::Right, 0, 0);
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, 0, 0), Some(Sprite::Flag));
+        assert!(game.renderer.texts().is_empty());
+        click(&mut game, MouseButton::Right, 0, 0);
+        game.draw().unwrap();
+        assert_eq!(game.renderer.texts(), vec!["2"]);
+        click(&mut game, MouseButton::Right, 0, 0);
+        click(&mut game, MouseButton::Right, 0, 0);
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, 0, 0), Some(Sprite::Unknown));
+
+        // the loss shows how many mines the cell held
+        click(&mut game, MouseButton::Left, 0, 0);
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, 0, 0), Some(Sprite::BombExploded));
+        assert!(game.renderer.texts().contains(&"3"));
+    }
+
+    #[test]
+    fn holes_are_not_drawn() {
+        let model = crate::game_model::layout::parse("*-.\n.--").unwrap();
+        let mut game = create_game(model);
+        click(&mut game, MouseButton::Left, 0, 1);
+        click(&mut game, MouseButton::Right, 1, 1);
+        game.draw().unwrap();
+        assert_eq!(sprite_at_cell(&game, 0, 1), None);
+        assert_eq!(sprite_at_cell(&game, 1, 2), None);
+        assert_eq!(sprite_at_cell(&game, 0, 2), Some(Sprite::Unknown));
+        assert_eq!(game.model.moves(), &[]);
+        // the cursor jumps over holes
+        game.cursor = (0, 2);
+        game.move_cursor_to_covered();
+        assert_eq!(game.cursor, (1, 0));
+    }
+
+    #[test]
+    fn puzzles_navigation_and_progress() {
+        let files = [("a", "o.*\n..*"), ("b", "*.o")]
+            .map(|(name, text)| (name.to_string(), text.to_string()));
+        let pack = PuzzlePack::new("test", &files).unwrap();
+        let mut game = create_game(pack.current().model());
+        game.set_puzzles(pack);
+        game.draw().unwrap();
+        assert_eq!(game.renderer.texts(), vec!["Puzzle 1/2: a  Solved: 0"]);
+
+        click(&mut game, MouseButton::Left, 1, 0);
+        assert_eq!(game.model.state(), State::Win);
+        let key = |game: &mut Minesweeper<RecordingRenderer, ScriptedInput>, keycode| {
+            game.input.events.push_back(InputEvent::KeyDown(keycode));
+            game.handle_events();
+        };
+        // restart brings back the opened cells of the puzzle
+        key(&mut game, Keycode::Space);
+        assert_eq!(game.model.state(), State::InProgress);
+        assert!(game.model.get_cell(0, 0).is_visible());
+        assert!(!game.model.get_cell(1, 0).is_visible());
+
+        key(&mut game, Keycode::RightBracket);
+        assert_eq!(game.model.board_size(), (1, 3));
+        key(&mut game, Keycode::RightBracket);
+        game.draw().unwrap();
+        assert_eq!(game.renderer.texts(), vec!["Puzzle 2/2: b  Solved: 1"]);
+        key(&mut game, Keycode::Comma);
+        assert_eq!(game.model.board_size(), (2, 3));
+    }
+
+    #[test]
+    fn escape_stops_game() {
+        let mut model = GameModel::new();
+        model.start(4, 4, 1).unwrap();
+        let mut game = create_game(model);
+        game.is_running = true;
+        game.input
+            .events
+            .push_back(InputEvent::KeyDown(Keycode::Escape));
+        game.handle_events();
+        assert!(!game.is_running);
     }
 }
@@ -0,0 +1,235 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::game_model::{layout, GameModel, State};
+use crate::solver;
+
+// Solved puzzles of all packs in the working directory, one `<pack>/<puzzle>` per line
+const PROGRESS_FILE: &str = "puzzles_solved.txt";
+
+// Hand-made board: fixed mines and the cells opened at the start, in the layout format
+pub struct Puzzle {
+    name: String,
+    model: GameModel,
+}
+
+impl Puzzle {
+    // Only puzzles in progress that the solver finishes without guessing are accepted. The
+    // count of mines isn't shown to the player, so the numbers alone have to be enough
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let model = layout::parse(text)?;
+        if model.state() != State::InProgress {
+            return Err("Puzzle is already finished".to_string());
+        }
+        if !solver::is_solvable_without_guessing(&mut model.position_after(0)) {
+            return Err("Puzzle can't be solved without guessing".to_string());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            model,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Fresh game of the puzzle
+    pub fn model(&self) -> GameModel {
+        self.model.position_after(0)
+    }
+}
+
+// Puzzles of a directory played in the order of their file names
+pub struct PuzzlePack {
+    name: String,
+    puzzles: Vec<Puzzle>,
+    current: usize,
+    solved: HashSet<String>,
+    progress_path: Option<PathBuf>,
+}
+
+impl PuzzlePack {
+    // Reads the `.txt` files of the directory and the progress of the pack
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let error = |e: std::io::Error| format!("{}: {e}", dir.display());
+        let mut paths = fs::read_dir(dir)
+            .map_err(error)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        let mut files = Vec::new();
+        for path in paths {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            files.push((name.into_owned(), fs::read_to_string(&path).map_err(error)?));
+        }
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        let mut pack = Self::new(&name, &files)?;
+        let progress = fs::read_to_string(PROGRESS_FILE).unwrap_or_default();
+        pack.solved = progress
+            .lines()
+            .filter_map(|line| line.strip_prefix(&format!("{name}/")))
+            .map(str::to_string)
+            .collect();
+        pack.progress_path = Some(PathBuf::from(PROGRESS_FILE));
+        Ok(pack)
+    }
+
+    // Puzzles failing the validation are left out with a warning, the progress isn't saved
+    pub fn new(name: &str, files: &[(String, String)]) -> Result<Self, String> {
+        let mut puzzles = Vec::new();
+        for (puzzle_name, text) in files {
+            match Puzzle::parse(puzzle_name, text) {
+                Ok(puzzle) => puzzles.push(puzzle),
+                Err(e) => eprintln!("Puzzle '{name}/{puzzle_name}' is skipped: {e}"),
+            }
+        }
+        if puzzles.is_empty() {
+            return Err(format!("No valid puzzles in '{name}'"));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            puzzles,
+            current: 0,
+            solved: HashSet::new(),
+            progress_path: None,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.puzzles.len()
+    }
+
+    pub fn current(&self) -> &Puzzle {
+        &self.puzzles[self.current]
+    }
+
+    // Largest rows and columns among the puzzles
+    pub fn max_board_size(&self) -> (usize, usize) {
+        self.puzzles.iter().fold((0, 0), |(rows, cols), puzzle| {
+            let size = puzzle.model.board_size();
+            (rows.max(size.0), cols.max(size.1))
+        })
+    }
+
+    // Starts with the first unsolved puzzle
+    pub fn skip_solved(&mut self) {
+        self.current = (0..self.len())
+            .find(|&index| !self.is_solved(index))
+            .unwrap_or(0);
+    }
+
+    // Moves to the next or previous puzzle, false at the ends of the pack
+    pub fn go_next(&mut self) -> bool {
+        let is_moved = self.current + 1 < self.len();
+        self.current += is_moved as usize;
+        is_moved
+    }
+
+    pub fn go_previous(&mut self) -> bool {
+        let is_moved = self.current > 0;
+        self.current -= is_moved as usize;
+        is_moved
+    }
+
+    pub fn is_solved(&self, index: usize) -> bool {
+        self.solved.contains(self.puzzles[index].name())
+    }
+
+    pub fn mark_solved(&mut self) {
+        let name = self.current().name().to_string();
+        if !self.solved.insert(name.clone()) {
+            return;
+        }
+        let Some(path) = &self.progress_path else {
+            return;
+        };
+        let saved = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}/{name}", self.name));
+        if let Err(e) = saved {
+            eprintln!("Puzzle progress is not saved: {e}");
+        }
+    }
+
+    // Position in the pack and progress for the status line
+    pub fn status(&self) -> String {
+        let solved = (0..self.len())
+            .filter(|&index| self.is_solved(index))
+            .count();
+        let mark = if self.is_solved(self.current) {
+            ", solved"
+        } else {
+            ""
+        };
+        format!(
+            "Puzzle {}/{}: {}{mark}  Solved: {solved}",
+            self.current + 1,
+            self.len(),
+            self.current().name(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::asset_path;
+
+    fn files(texts: &[&str]) -> Vec<(String, String)> {
+        texts
+            .iter()
+            .enumerate()
+            .map(|(index, text)| (format!("{index}"), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn puzzle_pack_skips_invalid() {
+        // the second one needs a guess, the third one is won already
+        let pack = PuzzlePack::new("test", &files(&["o.*\n..*", "o.*\n...", "o*"])).unwrap();
+        assert_eq!(pack.len(), 1);
+        assert_eq!(pack.current().name(), "0");
+        assert!(pack.current().model().get_cell(0, 0).is_visible());
+        assert!(PuzzlePack::new("test", &files(&["o.*\n...", "o?"])).is_err());
+    }
+
+    #[test]
+    fn puzzle_pack_navigation() {
+        let mut pack = PuzzlePack::new("test", &files(&["o.*\n..*", "*.o", "o.*"])).unwrap();
+        assert_eq!(pack.max_board_size(), (2, 3));
+        assert!(!pack.go_previous());
+        pack.mark_solved();
+        assert!(pack.go_next());
+        assert_eq!(pack.status(), "Puzzle 2/3: 1  Solved: 1");
+        pack.skip_solved();
+        assert_eq!(pack.current().name(), "1");
+        assert!(pack.go_next() && !pack.go_next());
+        pack.mark_solved();
+        assert_eq!(pack.status(), "Puzzle 3/3: 2, solved  Solved: 2");
+    }
+
+    #[test]
+    fn puzzle_pack_assets() {
+        // every puzzle of the bundled packs is valid
+        for pack in fs::read_dir(asset_path("puzzles")).unwrap() {
+            let dir = pack.unwrap().path();
+            let mut count = 0;
+            for file in fs::read_dir(&dir).unwrap() {
+                let path = file.unwrap().path();
+                let text = fs::read_to_string(&path).unwrap();
+                let name = path.display().to_string();
+                if let Err(e) = Puzzle::parse(&name, &text) {
+                    panic!("{name}: {e}");
+                }
+                count += 1;
+            }
+            assert_eq!(PuzzlePack::load(&dir).unwrap().len(), count);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use crate::resources::Theme;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Sprite {
+    Empty,
+    Bomb,
+    BombExploded,
+    Flag,
+    // flag put on a safe cell, shown after the loss
+    WrongFlag,
+    Unknown,
+    Number(usize),
+}
+
+// Drawing target of the game screen
+pub trait Renderer {
+    fn clear(&mut self);
+    fn draw_sprite(&mut self, sprite: Sprite, frame: Rect) -> Result<(), String>;
+    fn fill_rect(&mut self, color: Color, frame: Rect) -> Result<(), String>;
+    fn draw_text(&mut self, text: &str, color: Color, frame: Rect) -> Result<(), String>;
+    // Replaces the kept minimap image, `pixels` holds a color per board cell row by row
+    fn update_minimap(&mut self, board: (usize, usize), pixels: &[Color]) -> Result<(), String>;
+    // Draws the kept minimap image stretched over the frame
+    fn draw_minimap(&mut self, frame: Rect) -> Result<(), String>;
+    // Covers the parts of the frame outside the hexagon inscribed in it, cutting the square
+    // cell drawn there to a hex cell
+    fn draw_hex_mask(&mut self, color: Color, frame: Rect) -> Result<(), String>;
+    // Saves content drawn so far into PNG file
+    fn save_screenshot(&mut self, path: &Path) -> Result<(), String>;
+    fn present(&mut self);
+    fn set_theme(&mut self, theme: &Theme);
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum InputEvent {
+    Quit,
+    KeyDown(Keycode),
+    Resized(u32, u32),
+    // positive values scroll away from the user
+    MouseWheel(i32),
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct MouseSnapshot {
+    pub x: i32,
+    pub y: i32,
+    pub buttons: HashSet<MouseButton>,
+}
+
+// Source of user input of the game screen
+pub trait InputSource {
+    fn poll_events(&mut self) -> Vec<InputEvent>;
+    fn mouse_state(&self) -> MouseSnapshot;
+}
+
+// Input source for screens rendered without user interaction
+pub struct NoInput;
+
+impl InputSource for NoInput {
+    fn poll_events(&mut self) -> Vec<InputEvent> {
+        Vec::new()
+    }
+
+    fn mouse_state(&self) -> MouseSnapshot {
+        MouseSnapshot::default()
+    }
+}
@@ -0,0 +1,330 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use super::Sprite;
+
+// Built-in graphics used when sprites or fonts can't be loaded, everything
+// is drawn with filled rectangles so any renderer is able to show it
+
+// Sprites are designed on the grid of GRID x GRID units scaled to the cell frame
+const GRID: f64 = 16.0;
+
+const COVERED: Color = Color::RGB(192, 192, 192);
+const OPENED: Color = Color::RGB(208, 208, 208);
+const LIGHT: Color = Color::RGB(255, 255, 255);
+const SHADOW: Color = Color::RGB(128, 128, 128);
+const EXPLODED: Color = Color::RGB(255, 0, 0);
+const BLACK: Color = Color::RGB(0, 0, 0);
+const FLAG: Color = Color::RGB(230, 0, 0);
+
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+// 5x7 bitmap font, each row is 5 bits starting from the left pixel
+#[rustfmt::skip]
+const GLYPHS: [(char, [u8; 7]); 48] = [
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+];
+
+// Filled rectangles drawing the sprite in the frame
+pub fn sprite_shapes(sprite: Sprite, frame: Rect) -> Vec<(Color, Rect)> {
+    let grid = Grid::new(frame);
+    let mut shapes = Vec::new();
+    match sprite {
+        Sprite::Unknown => covered(&grid, &mut shapes),
+        Sprite::Flag => {
+            covered(&grid, &mut shapes);
+            flag(&grid, &mut shapes);
+        }
+        Sprite::Empty => opened(&grid, OPENED, &mut shapes),
+        Sprite::Number(count) => {
+            opened(&grid, OPENED, &mut shapes);
+            let color = number_color(count);
+            shapes.extend(text_shapes(&count.to_string(), color, number_frame(frame)));
+        }
+        Sprite::Bomb => {
+            opened(&grid, OPENED, &mut shapes);
+            bomb(&grid, &mut shapes);
+        }
+        Sprite::BombExploded => {
+            opened(&grid, EXPLODED, &mut shapes);
+            bomb(&grid, &mut shapes);
+        }
+        Sprite::WrongFlag => {
+            opened(&grid, OPENED, &mut shapes);
+            bomb(&grid, &mut shapes);
+            cross(&grid, &mut shapes);
+        }
+    }
+    shapes
+}
+
+// Filled rectangles drawing the text with the built-in font centered in the frame
+pub fn text_shapes(text: &str, color: Color, frame: Rect) -> Vec<(Color, Rect)> {
+    let glyphs = text.chars().map(glyph).collect::<Vec<_>>();
+    if glyphs.is_empty() {
+        return Vec::new();
+    }
+    // one pixel gap between glyphs
+    let width = glyphs.len() as u32 * (GLYPH_WIDTH + 1) - 1;
+    let scale = (frame.width() / width)
+        .min(frame.height() / GLYPH_HEIGHT)
+        .max(1);
+    let x0 = frame.x() + (frame.width() as i32 - (width * scale) as i32) / 2;
+    let y0 = frame.y() + (frame.height() as i32 - (GLYPH_HEIGHT * scale) as i32) / 2;
+    let scale = scale as i32;
+
+    let mut shapes = Vec::new();
+    for (index, rows) in glyphs.iter().enumerate() {
+        let left = x0 + index as i32 * (GLYPH_WIDTH as i32 + 1) * scale;
+        for (y, bits) in rows.iter().enumerate() {
+            // neighbour pixels of the row are merged into one rectangle
+            let mut x = 0;
+            while x < GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - x)) == 0 {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < GLYPH_WIDTH && bits & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                    x += 1;
+                }
+                let rect = Rect::new(
+                    left + start as i32 * scale,
+                    y0 + y as i32 * scale,
+                    (x - start) * scale as u32,
+                    scale as u32,
+                );
+                shapes.push((color, rect));
+            }
+        }
+    }
+    shapes
+}
+
+fn glyph(symbol: char) -> [u8; 7] {
+    let symbol = symbol.to_ascii_uppercase();
+    GLYPHS
+        .iter()
+        .find(|(key, _)| *key == symbol)
+        .or_else(|| GLYPHS.iter().find(|(key, _)| *key == '?'))
+        .map(|(_, rows)| *rows)
+        .unwrap_or_default()
+}
+
+// Part of the cell taken by the digits of its number
+pub fn number_frame(frame: Rect) -> Rect {
+    Grid::new(frame).rect(3.0, 2.0, 10.0, 12.0)
+}
+
+// Classic colors, repeated for numbers above eight
+pub fn number_color(count: usize) -> Color {
+    match (count + 7) % 8 + 1 {
+        1 => Color::RGB(0, 0, 255),
+        2 => Color::RGB(0, 128, 0),
+        3 => Color::RGB(255, 0, 0),
+        4 => Color::RGB(0, 0, 128),
+        5 => Color::RGB(128, 0, 0),
+        6 => Color::RGB(0, 128, 128),
+        8 => Color::RGB(96, 96, 96),
+        _ => BLACK,
+    }
+}
+
+fn covered(grid: &Grid, shapes: &mut Vec<(Color, Rect)>) {
+    shapes.push((COVERED, grid.frame));
+    shapes.push((LIGHT, grid.rect(0.0, 0.0, GRID, 1.5)));
+    shapes.push((LIGHT, grid.rect(0.0, 0.0, 1.5, GRID)));
+    shapes.push((SHADOW, grid.rect(0.0, GRID - 1.5, GRID, 1.5)));
+    shapes.push((SHADOW, grid.rect(GRID - 1.5, 0.0, 1.5, GRID)));
+}
+
+fn opened(grid: &Grid, background: Color, shapes: &mut Vec<(Color, Rect)>) {
+    shapes.push((background, grid.frame));
+    shapes.push((SHADOW, grid.rect(0.0, 0.0, GRID, 0.5)));
+    shapes.push((SHADOW, grid.rect(0.0, 0.0, 0.5, GRID)));
+}
+
+fn flag(grid: &Grid, shapes: &mut Vec<(Color, Rect)>) {
+    // triangle pointing left from the pole
+    for (row, width) in [1.0, 3.0, 5.0, 3.0, 1.0].into_iter().enumerate() {
+        shapes.push((FLAG, grid.rect(8.0 - width, 3.0 + row as f64, width, 1.0)));
+    }
+    shapes.push((BLACK, grid.rect(8.0, 3.0, 1.0, 9.0)));
+    shapes.push((BLACK, grid.rect(5.0, 11.0, 7.0, 1.0)));
+    shapes.push((BLACK, grid.rect(4.0, 12.0, 9.0, 1.5)));
+}
+
+fn bomb(grid: &Grid, shapes: &mut Vec<(Color, Rect)>) {
+    shapes.push((BLACK, grid.rect(2.5, 7.5, 11.0, 1.0)));
+    shapes.push((BLACK, grid.rect(7.5, 2.5, 1.0, 11.0)));
+    // body drawn by horizontal lines of pixels
+    let frame = grid.frame;
+    let radius = 4.5 * grid.unit_height;
+    let center_x = frame.x() as f64 + frame.width() as f64 / 2.0;
+    let center_y = frame.y() as f64 + frame.height() as f64 / 2.0;
+    let top = (center_y - radius).round() as i32;
+    let bottom = (center_y + radius).round() as i32;
+    for y in top..bottom {
+        let dy = y as f64 + 0.5 - center_y;
+        let half = (radius * radius - dy * dy).max(0.0).sqrt() * grid.unit_width / grid.unit_height;
+        let width = (half * 2.0).round() as u32;
+        if width > 0 {
+            let x = (center_x - half).round() as i32;
+            shapes.push((BLACK, Rect::new(x, y, width, 1)));
+        }
+    }
+    shapes.push((LIGHT, grid.rect(5.5, 5.5, 1.5, 1.5)));
+}
+
+// Diagonal lines drawn by small squares
+fn cross(grid: &Grid, shapes: &mut Vec<(Color, Rect)>) {
+    for step in 0..20 {
+        let offset = 2.0 + step as f64 * 0.55;
+        shapes.push((FLAG, grid.rect(offset, offset, 1.5, 1.5)));
+        shapes.push((FLAG, grid.rect(GRID - 1.5 - offset, offset, 1.5, 1.5)));
+    }
+}
+
+struct Grid {
+    frame: Rect,
+    unit_width: f64,
+    unit_height: f64,
+}
+
+impl Grid {
+    fn new(frame: Rect) -> Self {
+        Self {
+            frame,
+            unit_width: frame.width() as f64 / GRID,
+            unit_height: frame.height() as f64 / GRID,
+        }
+    }
+
+    // Rectangle in grid units, never thinner than a pixel
+    fn rect(&self, x: f64, y: f64, width: f64, height: f64) -> Rect {
+        let left = (x * self.unit_width).round() as i32;
+        let top = (y * self.unit_height).round() as i32;
+        let right = ((x + width) * self.unit_width).round() as i32;
+        let bottom = ((y + height) * self.unit_height).round() as i32;
+        Rect::new(
+            self.frame.x() + left,
+            self.frame.y() + top,
+            (right - left).max(1) as u32,
+            (bottom - top).max(1) as u32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_sprites_stay_in_frame() {
+        let mut sprites = vec![
+            Sprite::Empty,
+            Sprite::Bomb,
+            Sprite::BombExploded,
+            Sprite::Flag,
+            Sprite::WrongFlag,
+            Sprite::Unknown,
+        ];
+        sprites.extend((1..=8).map(Sprite::Number));
+        for size in [8, 16, 33, 64] {
+            let frame = Rect::new(10, 20, size, size);
+            for &sprite in &sprites {
+                let shapes = sprite_shapes(sprite, frame);
+                assert_eq!(shapes[0].1, frame, "{sprite:?} should fill the cell");
+                for (_, rect) in shapes {
+                    assert!(
+                        frame.contains_rect(rect),
+                        "{sprite:?} {rect:?} is out of {frame:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fallback_sprites_differ() {
+        let frame = Rect::new(0, 0, 64, 64);
+        let numbers = (1..=8)
+            .map(|count| sprite_shapes(Sprite::Number(count), frame))
+            .collect::<Vec<_>>();
+        for (i, a) in numbers.iter().enumerate() {
+            for b in &numbers[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        assert_ne!(
+            sprite_shapes(Sprite::Bomb, frame),
+            sprite_shapes(Sprite::BombExploded, frame)
+        );
+    }
+
+    #[test]
+    fn fallback_text_is_centered_and_scaled() {
+        let frame = Rect::new(0, 0, 290, 70);
+        let shapes = text_shapes("lt", Color::RGB(1, 2, 3), frame);
+        // "L" and "T" are 11 pixels wide with the gap, scaled by 10
+        let left = shapes.iter().map(|(_, r)| r.left()).min().unwrap();
+        let right = shapes.iter().map(|(_, r)| r.right()).max().unwrap();
+        assert_eq!((left, right), (90, 200));
+        assert!(shapes
+            .iter()
+            .all(|(c, r)| *c == Color::RGB(1, 2, 3) && frame.contains_rect(*r)));
+        // unknown symbols are drawn as question marks
+        assert_eq!(
+            text_shapes("~", BLACK, frame),
+            text_shapes("?", BLACK, frame)
+        );
+    }
+}
@@ -0,0 +1,13 @@
+mod backend;
+pub use backend::*;
+
+mod fallback;
+pub use fallback::*;
+
+mod sdl;
+pub use sdl::*;
+
+#[cfg(test)]
+mod recording;
+#[cfg(test)]
+pub use recording::*;
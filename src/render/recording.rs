@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use super::{InputEvent, InputSource, MouseSnapshot, Renderer, Sprite};
+use crate::resources::Theme;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum DrawCommand {
+    Clear,
+    Sprite(Sprite, Rect),
+    FillRect(Color, Rect),
+    Text(String, Rect),
+    Minimap(Rect),
+    HexMask(Rect),
+    Screenshot(PathBuf),
+    Present,
+}
+
+// In-memory renderer remembering every drawing call of the last frame
+#[derive(Default)]
+pub struct RecordingRenderer {
+    pub commands: Vec<DrawCommand>,
+    pub theme_name: Option<String>,
+    // image of the last minimap update
+    pub minimap: Vec<Color>,
+    pub minimap_updates: usize,
+}
+
+impl RecordingRenderer {
+    // Topmost sprite drawn at the given point
+    pub fn sprite_at(&self, x: i32, y: i32) -> Option<Sprite> {
+        self.commands
+            .iter()
+            .rev()
+            .find_map(|command| match command {
+                DrawCommand::Sprite(sprite, frame) if frame.contains_point((x, y)) => Some(*sprite),
+                _ => None,
+            })
+    }
+
+    pub fn texts(&self) -> Vec<&str> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Text(text, _) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn clear(&mut self) {
+        self.commands.clear();
+        self.commands.push(DrawCommand::Clear);
+    }
+
+    fn draw_sprite(&mut self, sprite: Sprite, frame: Rect) -> Result<(), String> {
+        self.commands.push(DrawCommand::Sprite(sprite, frame));
+        Ok(())
+    }
+
+    fn fill_rect(&mut self, color: Color, frame: Rect) -> Result<(), String> {
+        self.commands.push(DrawCommand::FillRect(color, frame));
+        Ok(())
+    }
+
+    fn draw_text(&mut self, text: &str, _color: Color, frame: Rect) -> Result<(), String> {
+        self.commands
+            .push(DrawCommand::Text(text.to_string(), frame));
+        Ok(())
+    }
+
+    fn update_minimap(&mut self, _board: (usize, usize), pixels: &[Color]) -> Result<(), String> {
+        self.minimap = pixels.to_vec();
+        self.minimap_updates += 1;
+        Ok(())
+    }
+
+    fn draw_minimap(&mut self, frame: Rect) -> Result<(), String> {
+        self.commands.push(DrawCommand::Minimap(frame));
+        Ok(())
+    }
+
+    fn draw_hex_mask(&mut self, _color: Color, frame: Rect) -> Result<(), String> {
+        self.commands.push(DrawCommand::HexMask(frame));
+        Ok(())
+    }
+
+    fn save_screenshot(&mut self, path: &Path) -> Result<(), String> {
+        self.commands
+            .push(DrawCommand::Screenshot(path.to_path_buf()));
+        Ok(())
+    }
+
+    fn present(&mut self) {
+        self.commands.push(DrawCommand::Present);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme_name = Some(theme.name().to_string());
+    }
+}
+
+// Input replaying prepared events and mouse state
+#[derive(Default)]
+pub struct ScriptedInput {
+    pub events: VecDeque<InputEvent>,
+    pub mouse: MouseSnapshot,
+}
+
+impl InputSource for ScriptedInput {
+    fn poll_events(&mut self) -> Vec<InputEvent> {
+        self.events.drain(..).collect()
+    }
+
+    fn mouse_state(&self) -> MouseSnapshot {
+        self.mouse.clone()
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::image::SaveSurface;
+use sdl2::mouse::MouseWheelDirection;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use sdl2::EventPump;
+
+use super::{
+    number_color, number_frame, sprite_shapes, text_shapes, InputEvent, InputSource, MouseSnapshot,
+    Renderer, Sprite,
+};
+use crate::camera::is_in_hexagon;
+use crate::resources::{FontManager, FontProvider, TextureManager, TextureProvider, Theme};
+
+// Draws either into a window or into an off-screen software surface
+pub struct SdlRenderer<'a, T: RenderTarget> {
+    canvas: Canvas<T>,
+    texture_creator: TextureCreator<T::Context>,
+    texture_manager: TextureManager<'a, T::Context>,
+    font_manager: FontManager<'a>,
+    theme: Theme,
+    // assets failed to load, built-in graphics is drawn instead
+    missing_sprites: HashSet<Sprite>,
+    missing_font: bool,
+    // one pixel per board cell, updated only when the board changes
+    minimap: Option<Texture<'a>>,
+    // hexagon mask of the cell size and the gap color it was made for
+    hex_mask: Option<(Rect, Color, Texture<'a>)>,
+}
+
+impl<'a, T: RenderTarget> SdlRenderer<'a, T> {
+    pub fn new(
+        canvas: Canvas<T>,
+        texture_creator: TextureCreator<T::Context>,
+        texture_manager: TextureManager<'a, T::Context>,
+        font_manager: FontManager<'a>,
+        theme: Theme,
+    ) -> Self {
+        Self {
+            canvas,
+            texture_creator,
+            texture_manager,
+            font_manager,
+            theme,
+            missing_sprites: HashSet::new(),
+            missing_font: false,
+            minimap: None,
+            hex_mask: None,
+        }
+    }
+
+    // Texture of the given size and format filled with the pixels
+    fn create_texture(&self, size: (u32, u32), pixels: &[Color]) -> Result<Texture<'a>, String> {
+        let mut texture = self
+            .texture_manager
+            .loader()
+            .create_texture_streaming(PixelFormatEnum::RGBA32, size.0, size.1)
+            .map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+        let bytes = pixels
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b, color.a])
+            .collect::<Vec<_>>();
+        let pitch = PixelFormatEnum::RGBA32.byte_size_of_pixels(size.0 as usize);
+        texture
+            .update(None, &bytes, pitch)
+            .map_err(|e| e.to_string())?;
+        Ok(texture)
+    }
+
+    fn fill_shapes(&mut self, shapes: Vec<(Color, Rect)>) -> Result<(), String> {
+        for (color, rect) in shapes {
+            self.fill_rect(color, rect)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: RenderTarget> Renderer for SdlRenderer<'a, T> {
+    fn clear(&mut self) {
+        self.canvas.clear();
+    }
+
+    fn draw_sprite(&mut self, sprite: Sprite, frame: Rect) -> Result<(), String> {
+        if let Sprite::Number(count) = sprite {
+            // numbers without an image of their own are written over the opened cell
+            if !self.theme.has_sprite(sprite) {
+                self.draw_sprite(Sprite::Empty, frame)?;
+                let text = count.to_string();
+                return self.draw_text(&text, number_color(count), number_frame(frame));
+            }
+        }
+        if !self.missing_sprites.contains(&sprite) {
+            match self.texture_manager.img_sprite(&self.theme, sprite) {
+                Ok(texture) => return self.canvas.copy(&texture, None, frame),
+                Err(e) => {
+                    eprintln!("Warning: sprite {sprite:?} is not loaded ({e}), built-in is drawn");
+                    self.missing_sprites.insert(sprite);
+                }
+            }
+        }
+        self.fill_shapes(sprite_shapes(sprite, frame))
+    }
+
+    fn fill_rect(&mut self, color: Color, frame: Rect) -> Result<(), String> {
+        let blend_mode = if color.a < u8::MAX {
+            BlendMode::Blend
+        } else {
+            BlendMode::None
+        };
+        self.canvas.set_blend_mode(blend_mode);
+        self.canvas.set_draw_color(color);
+        self.canvas.fill_rect(frame)?;
+        self.canvas.set_blend_mode(BlendMode::None);
+        Ok(())
+    }
+
+    fn draw_text(&mut self, text: &str, color: Color, frame: Rect) -> Result<(), String> {
+        if self.missing_font {
+            return self.fill_shapes(text_shapes(text, color, frame));
+        }
+        let font = match self.font_manager.font_header(&self.theme) {
+            Ok(font) => font,
+            Err(e) => {
+                eprintln!("Warning: font is not loaded ({e}), built-in is drawn");
+                self.missing_font = true;
+                return self.fill_shapes(text_shapes(text, color, frame));
+            }
+        };
+        let surface = font
+            .render(text)
+            .blended(color)
+            .map_err(|e| e.to_string())?;
+        let texture = self
+            .texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())?;
+        self.canvas.copy(&texture, None, Some(frame))
+    }
+
+    fn update_minimap(&mut self, board: (usize, usize), pixels: &[Color]) -> Result<(), String> {
+        let (rows, cols) = board;
+        self.minimap = Some(self.create_texture((cols as u32, rows as u32), pixels)?);
+        Ok(())
+    }
+
+    fn draw_minimap(&mut self, frame: Rect) -> Result<(), String> {
+        match &self.minimap {
+            Some(texture) => self.canvas.copy(texture, None, frame),
+            None => Ok(()),
+        }
+    }
+
+    fn draw_hex_mask(&mut self, color: Color, frame: Rect) -> Result<(), String> {
+        let size = (frame.width(), frame.height());
+        let is_outdated = self
+            .hex_mask
+            .as_ref()
+            .is_none_or(|(mask, mask_color, _)| mask.size() != size || *mask_color != color);
+        if is_outdated {
+            let transparent = Color::RGBA(0, 0, 0, 0);
+            let pixels = (0..size.0 * size.1)
+                .map(|index| {
+                    let (x, y) = ((index % size.0) as i32, (index / size.0) as i32);
+                    if is_in_hexagon(size, x, y) {
+                        transparent
+                    } else {
+                        color
+                    }
+                })
+                .collect::<Vec<_>>();
+            let texture = self.create_texture(size, &pixels)?;
+            self.hex_mask = Some((frame, color, texture));
+        }
+        match &self.hex_mask {
+            Some((_, _, texture)) => self.canvas.copy(texture, None, frame),
+            None => Ok(()),
+        }
+    }
+
+    fn save_screenshot(&mut self, path: &Path) -> Result<(), String> {
+        let (width, height) = self.canvas.output_size()?;
+        let format = PixelFormatEnum::RGBA32;
+        let mut pixels = self.canvas.read_pixels(None, format)?;
+        let pitch = format.byte_size_of_pixels(width as usize) as u32;
+        let surface = Surface::from_data(&mut pixels, width, height, pitch, format)?;
+        surface.save(path)
+    }
+
+    fn present(&mut self) {
+        self.canvas.present();
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.texture_manager.clear();
+        self.font_manager.clear();
+        self.missing_sprites.clear();
+        self.missing_font = false;
+    }
+}
+
+pub struct SdlInput {
+    event_pump: EventPump,
+}
+
+impl SdlInput {
+    pub fn new(event_pump: EventPump) -> Self {
+        Self { event_pump }
+    }
+}
+
+impl InputSource for SdlInput {
+    fn poll_events(&mut self) -> Vec<InputEvent> {
+        self.event_pump
+            .poll_iter()
+            .filter_map(|event| match event {
+                Event::Quit { .. } => Some(InputEvent::Quit),
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } => Some(InputEvent::KeyDown(keycode)),
+                Event::MouseWheel { y, direction, .. } => {
+                    let steps = if direction == MouseWheelDirection::Flipped {
+                        -y
+                    } else {
+                        y
+                    };
+                    Some(InputEvent::MouseWheel(steps))
+                }
+                Event::Window {
+                    win_event: WindowEvent::SizeChanged(width, height),
+                    ..
+                } => Some(InputEvent::Resized(
+                    width.max(0) as u32,
+                    height.max(0) as u32,
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn mouse_state(&self) -> MouseSnapshot {
+        let state = self.event_pump.mouse_state();
+        MouseSnapshot {
+            x: state.x(),
+            y: state.y(),
+            buttons: state.pressed_mouse_buttons().collect(),
+        }
+    }
+}
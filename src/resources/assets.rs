@@ -0,0 +1,102 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const ASSETS_ENV: &str = "MINESWEEPER_ASSETS";
+const ASSETS_DIR: &str = "assets";
+const APP_DIR: &str = "minesweeper";
+
+// Assets directory found by the first existing of:
+// $MINESWEEPER_ASSETS, ./assets, assets next to the executable,
+// minesweeper/assets in $XDG_DATA_HOME and $XDG_DATA_DIRS
+pub fn assets_dir() -> Option<&'static Path> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let exe_dir = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        let dir = assets_candidates(|key| env::var(key).ok(), exe_dir)
+            .into_iter()
+            .find(|dir| dir.is_dir());
+        if dir.is_none() {
+            eprintln!(
+                "Warning: assets directory is not found, only embedded default theme is available"
+            );
+        }
+        dir
+    })
+    .as_deref()
+}
+
+// Path of the file in assets directory
+pub fn asset_path(relative: &str) -> PathBuf {
+    assets_dir().unwrap_or(Path::new(ASSETS_DIR)).join(relative)
+}
+
+fn assets_candidates(
+    var: impl Fn(&str) -> Option<String>,
+    exe_dir: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    candidates.extend(var(ASSETS_ENV).map(PathBuf::from));
+    candidates.push(PathBuf::from(ASSETS_DIR));
+    candidates.extend(exe_dir.map(|dir| dir.join(ASSETS_DIR)));
+    let data_home = var("XDG_DATA_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|home| Path::new(&home).join(".local/share")));
+    candidates.extend(data_home.map(|dir| dir.join(APP_DIR).join(ASSETS_DIR)));
+    let data_dirs = var("XDG_DATA_DIRS")
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    candidates.extend(
+        data_dirs
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| Path::new(dir).join(APP_DIR).join(ASSETS_DIR)),
+    );
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assets_candidates_order() {
+        let var = |key: &str| match key {
+            "MINESWEEPER_ASSETS" => Some("/custom".to_string()),
+            "HOME" => Some("/home/user".to_string()),
+            "XDG_DATA_DIRS" => Some("/opt/share:".to_string()),
+            _ => None,
+        };
+        let candidates = assets_candidates(var, Some(PathBuf::from("/bin")));
+        let expected = [
+            "/custom",
+            "assets",
+            "/bin/assets",
+            "/home/user/.local/share/minesweeper/assets",
+            "/opt/share/minesweeper/assets",
+        ];
+        assert_eq!(
+            candidates,
+            expected.iter().map(PathBuf::from).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn assets_candidates_xdg_defaults() {
+        let var = |key: &str| (key == "XDG_DATA_HOME").then(|| "/data".to_string());
+        let candidates = assets_candidates(var, None);
+        let expected = [
+            "assets",
+            "/data/minesweeper/assets",
+            "/usr/local/share/minesweeper/assets",
+            "/usr/share/minesweeper/assets",
+        ];
+        assert_eq!(
+            candidates,
+            expected.iter().map(PathBuf::from).collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,13 +1,132 @@
 use sdl2::pixels::Color;
 
-pub struct ColorManager;
+#[derive(Clone)]
+pub struct ColorManager {
+    title_message: Color,
+    fade: Color,
+    minimap_covered: Color,
+    minimap_opened: Color,
+    minimap_flag: Color,
+    minimap_viewport: Color,
+    explosion: Color,
+    analysis_safe: Color,
+    analysis_move: Color,
+    cursor: Color,
+    hover: Color,
+    ghost: Color,
+    hex_gap: Color,
+}
+
+impl Default for ColorManager {
+    fn default() -> Self {
+        Self {
+            title_message: Color::RGB(150, 150, 90),
+            fade: Color::RGBA(0, 0, 0, 170),
+            minimap_covered: Color::RGB(90, 90, 90),
+            minimap_opened: Color::RGB(200, 200, 200),
+            minimap_flag: Color::RGB(200, 40, 40),
+            minimap_viewport: Color::RGB(250, 220, 60),
+            explosion: Color::RGB(255, 140, 0),
+            analysis_safe: Color::RGBA(40, 200, 80, 110),
+            analysis_move: Color::RGB(250, 220, 60),
+            cursor: Color::RGB(60, 160, 250),
+            hover: Color::RGBA(255, 255, 255, 50),
+            ghost: Color::RGBA(0, 0, 0, 120),
+            hex_gap: Color::RGB(40, 40, 40),
+        }
+    }
+}
 
 impl ColorManager {
+    pub const NAMES: [&'static str; 13] = [
+        "title_message",
+        "fade",
+        "minimap_covered",
+        "minimap_opened",
+        "minimap_flag",
+        "minimap_viewport",
+        "explosion",
+        "analysis_safe",
+        "analysis_move",
+        "cursor",
+        "hover",
+        "ghost",
+        "hex_gap",
+    ];
+
+    // Returns false if there is no color with such name
+    pub fn set(&mut self, name: &str, color: Color) -> bool {
+        let field = match name {
+            "title_message" => &mut self.title_message,
+            "fade" => &mut self.fade,
+            "minimap_covered" => &mut self.minimap_covered,
+            "minimap_opened" => &mut self.minimap_opened,
+            "minimap_flag" => &mut self.minimap_flag,
+            "minimap_viewport" => &mut self.minimap_viewport,
+            "explosion" => &mut self.explosion,
+            "analysis_safe" => &mut self.analysis_safe,
+            "analysis_move" => &mut self.analysis_move,
+            "cursor" => &mut self.cursor,
+            "hover" => &mut self.hover,
+            "ghost" => &mut self.ghost,
+            "hex_gap" => &mut self.hex_gap,
+            _ => return false,
+        };
+        *field = color;
+        true
+    }
+
     pub fn title_message(&self) -> Color {
-        Color::RGB(150, 150, 90)
+        self.title_message
     }
 
     pub fn fade(&self) -> Color {
-        Color::RGBA(0, 0, 0, 170)
+        self.fade
+    }
+
+    pub fn minimap_covered(&self) -> Color {
+        self.minimap_covered
+    }
+
+    pub fn minimap_opened(&self) -> Color {
+        self.minimap_opened
+    }
+
+    pub fn minimap_flag(&self) -> Color {
+        self.minimap_flag
+    }
+
+    pub fn minimap_viewport(&self) -> Color {
+        self.minimap_viewport
+    }
+
+    pub fn explosion(&self) -> Color {
+        self.explosion
+    }
+
+    pub fn analysis_safe(&self) -> Color {
+        self.analysis_safe
+    }
+
+    pub fn analysis_move(&self) -> Color {
+        self.analysis_move
+    }
+
+    pub fn cursor(&self) -> Color {
+        self.cursor
+    }
+
+    pub fn hover(&self) -> Color {
+        self.hover
+    }
+
+    // Tint of the wrapped copies of cells beyond the edges of joined boards
+    pub fn ghost(&self) -> Color {
+        self.ghost
+    }
+
+    // Background between the cells of hex boards
+    pub fn hex_gap(&self) -> Color {
+        self.hex_gap
     }
 }
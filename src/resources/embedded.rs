@@ -0,0 +1,61 @@
+// Default assets compiled into the executable, so the game runs without the assets directory.
+// They are addressed by paths with the "embedded:" prefix and loaded from memory
+const PREFIX: &str = "embedded:";
+
+macro_rules! embed {
+    ($($path:literal),* $(,)?) => {
+        [$(($path, include_bytes!(concat!("../../assets/", $path)) as &[u8])),*]
+    };
+}
+
+const ASSETS: [(&str, &[u8]); 15] = embed![
+    "images/empty_64x64.png",
+    "images/bomb_64x64.png",
+    "images/bomb_exploded_64x64.png",
+    "images/flag_1_64x64.png",
+    "images/wrong_flag_64x64.png",
+    "images/unknown_1_64x64.png",
+    "images/1_64x64.png",
+    "images/2_64x64.png",
+    "images/3_64x64.png",
+    "images/4_64x64.png",
+    "images/5_64x64.png",
+    "images/6_64x64.png",
+    "images/7_64x64.png",
+    "images/8_64x64.png",
+    "fonts/Freedom-10eM.ttf",
+];
+
+// Path of the embedded asset, relative to the assets directory
+pub fn embedded_path(relative: &str) -> String {
+    format!("{PREFIX}{relative}")
+}
+
+// Data of the embedded asset if the path refers to one
+pub fn embedded_asset(path: &str) -> Option<Result<&'static [u8], String>> {
+    let relative = path.strip_prefix(PREFIX)?;
+    let data = ASSETS
+        .iter()
+        .find(|(name, _)| *name == relative)
+        .map(|(_, data)| *data)
+        .ok_or_else(|| format!("'{relative}' is not embedded"));
+    Some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_assets_match_files() {
+        for (name, data) in ASSETS {
+            let file = std::fs::read(format!("assets/{name}")).unwrap();
+            assert_eq!(file, data, "{name}");
+            assert_eq!(embedded_asset(&embedded_path(name)), Some(Ok(data)));
+        }
+        assert!(embedded_asset("assets/images/1_64x64.png").is_none());
+        assert!(embedded_asset(&embedded_path("missing.png"))
+            .unwrap()
+            .is_err());
+    }
+}
@@ -2,6 +2,7 @@ extern crate sdl2;
 
 use sdl2::image::LoadTexture;
 use sdl2::render::{Texture, TextureCreator};
+use sdl2::rwops::RWops;
 use sdl2::ttf::{Font, Sdl2TtfContext};
 
 use std::borrow::Borrow;
@@ -9,6 +10,8 @@ use std::collections::HashMap;
 use std::hash::Hash;
 use std::rc::Rc;
 
+use super::embedded_asset;
+
 // Generic trait to Load any Resource Kind
 pub trait ResourceLoader<'l, R> {
     type Args: ?Sized;
@@ -57,28 +60,43 @@ where
             Ok,
         )
     }
+
+    pub fn loader(&self) -> &'l L {
+        self.loader
+    }
+
+    // Drops cached resources, next requests load them again
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
 }
 
-// TextureCreator knows how to load Textures
+// TextureCreator knows how to load Textures, from files or embedded into the executable
 impl<'l, T> ResourceLoader<'l, Texture<'l>> for TextureCreator<T> {
     type Args = str;
-    fn load(&'l self, path: &str) -> Result<Texture, String> {
+    fn load(&'l self, path: &str) -> Result<Texture<'l>, String> {
         // println!("LOADED A TEXTURE");
-        self.load_texture(path)
+        match embedded_asset(path) {
+            Some(data) => self.load_texture_bytes(data?),
+            None => self.load_texture(path),
+        }
     }
 }
 
-// Font Context knows how to load Fonts
+// Font Context knows how to load Fonts, from files or embedded into the executable
 impl<'l> ResourceLoader<'l, Font<'l, 'static>> for Sdl2TtfContext {
     type Args = FontDetails;
     fn load(&'l self, details: &FontDetails) -> Result<Font<'l, 'static>, String> {
         // println!("LOADED A FONT");
-        self.load_font(&details.path, details.size)
+        match embedded_asset(&details.path) {
+            Some(data) => self.load_font_from_rwops(RWops::from_bytes(data?)?, details.size),
+            None => self.load_font(&details.path, details.size),
+        }
     }
 }
 
 // Information needed to load a Font
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct FontDetails {
     pub path: String,
     pub size: u16,
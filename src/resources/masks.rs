@@ -0,0 +1,78 @@
+use sdl2::image::LoadSurface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::asset_path;
+use crate::game_model::Mask;
+
+// Board shape from a text mask, see `Mask::parse`, or from a PNG image with one pixel per
+// cell: dark pixels are cells and light ones are holes. Paths not found as given are
+// looked up in the masks directory of the assets
+pub fn load_mask(path: &Path) -> Result<Mask, String> {
+    let path = mask_path(path);
+    let is_png = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    let mask = if is_png {
+        load_png(&path)
+    } else {
+        fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Mask::parse(&text))
+    };
+    mask.map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn mask_path(path: &Path) -> PathBuf {
+    if path.exists() || path.is_absolute() {
+        return path.to_path_buf();
+    }
+    asset_path("masks").join(path)
+}
+
+fn load_png(path: &Path) -> Result<Mask, String> {
+    let surface = Surface::from_file(path)?.convert_format(PixelFormatEnum::RGB24)?;
+    let (cols, rows) = (surface.width() as usize, surface.height() as usize);
+    let pitch = surface.pitch() as usize;
+    let mask = surface.with_lock(|pixels| mask_from_rgb(rows, cols, pitch, pixels));
+    if mask.cells_count() == 0 {
+        return Err("Mask has no cells".to_string());
+    }
+    Ok(mask)
+}
+
+// Rows of 3 byte pixels starting every `pitch` bytes
+fn mask_from_rgb(rows: usize, cols: usize, pitch: usize, pixels: &[u8]) -> Mask {
+    Mask::new(rows, cols, |row, col| {
+        let start = row * pitch + col * 3;
+        let [r, g, b] = [0, 1, 2].map(|i| pixels[start + i] as u32);
+        // perceived brightness
+        (299 * r + 587 * g + 114 * b) / 1000 < 128
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_from_rgb() {
+        // 2x2 image with rows padded to 8 bytes
+        let pixels = [
+            0, 0, 0, 255, 255, 255, 0, 0, //
+            40, 200, 40, 20, 20, 200, 0, 0,
+        ];
+        let mask = mask_from_rgb(2, 2, 8, &pixels);
+        assert_eq!(mask, Mask::new(2, 2, |row, col| row == col));
+    }
+
+    #[test]
+    fn masks_load_assets() {
+        let heart = load_mask(Path::new("heart.txt")).unwrap();
+        assert!(heart.is_hole(0, 0));
+        assert!(heart.cells_count() > 100);
+        assert!(load_mask(Path::new("no/such/mask.txt")).is_err());
+    }
+}
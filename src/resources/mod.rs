@@ -9,3 +9,15 @@ pub use fonts::*;
 
 mod colors;
 pub use colors::*;
+
+mod themes;
+pub use themes::*;
+
+mod assets;
+pub use assets::*;
+
+mod embedded;
+pub use embedded::*;
+
+mod masks;
+pub use masks::*;
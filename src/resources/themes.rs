@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sdl2::pixels::Color;
+
+use super::{asset_path, embedded_path, ColorManager, FontDetails};
+use crate::key_value;
+use crate::render::Sprite;
+
+pub fn themes_dir() -> PathBuf {
+    asset_path("themes")
+}
+
+// Theme directory contains descriptor file mapping sprites, colors and fonts to theme files:
+//   sprite.<empty|bomb|bomb_exploded|flag|wrong_flag|unknown|1..8> = <image path>
+//   sprite.<9..24> = <image path>, optional, the number is written with the font otherwise
+//   color.<name> = r, g, b[, a]
+//   font.header = <ttf path>
+//   font.header_size = <points>
+// Paths are relative to the theme directory
+const DESCRIPTOR: &str = "theme.cfg";
+// Largest number of mines around a cell, see `Grid`
+const MAX_NUMBER: usize = 24;
+
+#[derive(Clone)]
+pub struct Theme {
+    name: String,
+    sprites: HashMap<Sprite, String>,
+    colors: ColorManager,
+    header_font: FontDetails,
+}
+
+// Default theme built from embedded assets, fallback for anything missing in other themes
+impl Default for Theme {
+    fn default() -> Self {
+        let sprites = all_sprites()
+            .into_iter()
+            .map(|sprite| {
+                let file = match sprite {
+                    Sprite::Flag => "flag_1".to_string(),
+                    Sprite::Unknown => "unknown_1".to_string(),
+                    _ => sprite_key(sprite),
+                };
+                (sprite, embedded_path(&format!("images/{file}_64x64.png")))
+            })
+            .collect();
+        Self {
+            name: "classic".to_string(),
+            sprites,
+            colors: ColorManager::default(),
+            header_font: FontDetails {
+                path: embedded_path("fonts/Freedom-10eM.ttf"),
+                size: 170,
+            },
+        }
+    }
+}
+
+impl Theme {
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(DESCRIPTOR);
+        let text = fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let (theme, warnings) = Self::parse(dir, &text)?;
+        for warning in warnings {
+            eprintln!("Warning: theme '{}': {warning}", theme.name);
+        }
+        Ok(theme)
+    }
+
+    // Returns the theme and list of problems solved by falling back to the default theme
+    fn parse(dir: &Path, text: &str) -> Result<(Self, Vec<String>), String> {
+        let values = key_value::parse(text)?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let mut theme = Self::default();
+        let mut warnings = Vec::new();
+        theme.name = dir
+            .file_name()
+            .map_or(theme.name, |name| name.to_string_lossy().to_string());
+
+        let mut resolve = |key: &str| {
+            let Some(value) = values.get(key) else {
+                warnings.push(format!("'{key}' is not defined, default is used"));
+                return None;
+            };
+            let path = dir.join(value);
+            if !path.is_file() {
+                warnings.push(format!("'{}' not found, default is used", path.display()));
+                return None;
+            }
+            Some(path.to_string_lossy().to_string())
+        };
+        for sprite in all_sprites() {
+            if let Some(path) = resolve(&format!("sprite.{}", sprite_key(sprite))) {
+                theme.sprites.insert(sprite, path);
+            }
+        }
+        // larger neighbourhoods only
+        for sprite in (9..=MAX_NUMBER).map(Sprite::Number) {
+            let key = format!("sprite.{}", sprite_key(sprite));
+            if values.contains_key(&key) {
+                if let Some(path) = resolve(&key) {
+                    theme.sprites.insert(sprite, path);
+                }
+            }
+        }
+        if let Some(path) = resolve("font.header") {
+            theme.header_font.path = path;
+        }
+
+        if let Some(value) = values.get("font.header_size") {
+            theme.header_font.size = value
+                .parse()
+                .map_err(|_| format!("Invalid font size '{value}'"))?;
+        }
+        for name in ColorManager::NAMES {
+            match values.get(&format!("color.{name}")) {
+                Some(value) => {
+                    theme.colors.set(name, parse_color(value)?);
+                }
+                None => warnings.push(format!("'color.{name}' is not defined, default is used")),
+            }
+        }
+        Ok((theme, warnings))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sprite_path(&self, sprite: Sprite) -> &str {
+        self.sprites.get(&sprite).map_or("", |path| path.as_str())
+    }
+
+    pub fn has_sprite(&self, sprite: Sprite) -> bool {
+        self.sprites.contains_key(&sprite)
+    }
+
+    pub fn colors(&self) -> &ColorManager {
+        &self.colors
+    }
+
+    pub fn header_font(&self) -> &FontDetails {
+        &self.header_font
+    }
+}
+
+// Theme directories found in the root directory sorted by name
+pub fn available_themes(root: &Path) -> Vec<PathBuf> {
+    let mut themes = fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.join(DESCRIPTOR).is_file())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    themes.sort();
+    themes
+}
+
+// Loads theme by its directory name, falls back to the default theme if it fails
+pub fn find_theme(name: &str) -> Theme {
+    let dir = themes_dir().join(name);
+    Theme::load(&dir).unwrap_or_else(|e| {
+        eprintln!("Warning: theme '{name}' is not loaded ({e}), default is used");
+        Theme::default()
+    })
+}
+
+fn all_sprites() -> Vec<Sprite> {
+    let mut sprites = vec![
+        Sprite::Empty,
+        Sprite::Bomb,
+        Sprite::BombExploded,
+        Sprite::Flag,
+        Sprite::WrongFlag,
+        Sprite::Unknown,
+    ];
+    sprites.extend((1..=8).map(Sprite::Number));
+    sprites
+}
+
+fn sprite_key(sprite: Sprite) -> String {
+    match sprite {
+        Sprite::Empty => "empty".to_string(),
+        Sprite::Bomb => "bomb".to_string(),
+        Sprite::BombExploded => "bomb_exploded".to_string(),
+        Sprite::Flag => "flag".to_string(),
+        Sprite::WrongFlag => "wrong_flag".to_string(),
+        Sprite::Unknown => "unknown".to_string(),
+        Sprite::Number(count) => count.to_string(),
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    let components = value
+        .split(',')
+        .map(|component| component.trim().parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("Invalid color '{value}'"))?;
+    match components[..] {
+        [r, g, b] => Ok(Color::RGB(r, g, b)),
+        [r, g, b, a] => Ok(Color::RGBA(r, g, b, a)),
+        _ => Err(format!("Invalid color '{value}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_bundled_themes_are_complete() {
+        let themes = available_themes(&themes_dir());
+        assert!(themes.len() >= 2);
+        for dir in themes {
+            let text = fs::read_to_string(dir.join(DESCRIPTOR)).unwrap();
+            let (_, warnings) = Theme::parse(&dir, &text).unwrap();
+            assert!(warnings.is_empty(), "{}: {warnings:?}", dir.display());
+        }
+    }
+
+    #[test]
+    fn theme_falls_back_to_default() {
+        let dir = themes_dir().join("alternate");
+        let text = "sprite.flag = ../../images/flag_2_64x64.png
+            sprite.bomb = missing.png
+            sprite.12 = ../../images/flag_2_64x64.png
+            sprite.13 = missing_13.png
+            color.fade = 1, 2, 3, 4";
+        let (theme, warnings) = Theme::parse(&dir, text).unwrap();
+        let default = Theme::default();
+        assert_eq!(theme.name(), "alternate");
+        assert!(theme
+            .sprite_path(Sprite::Flag)
+            .ends_with("flag_2_64x64.png"));
+        assert_eq!(
+            theme.sprite_path(Sprite::Bomb),
+            default.sprite_path(Sprite::Bomb)
+        );
+        assert_eq!(theme.colors().fade(), Color::RGBA(1, 2, 3, 4));
+        assert_eq!(
+            theme.colors().title_message(),
+            default.colors().title_message()
+        );
+        assert!(warnings.iter().any(|w| w.contains("missing.png")));
+        assert!(warnings.iter().any(|w| w.contains("sprite.empty")));
+        // numbers above eight are optional
+        assert!(theme.has_sprite(Sprite::Number(12)));
+        assert!(!theme.has_sprite(Sprite::Number(13)));
+        assert!(!default.has_sprite(Sprite::Number(9)));
+        assert!(warnings.iter().any(|w| w.contains("missing_13.png")));
+        assert!(!warnings.iter().any(|w| w.contains("sprite.9")));
+    }
+
+    #[test]
+    fn theme_parse_color() {
+        assert_eq!(parse_color("1, 2, 3").unwrap(), Color::RGB(1, 2, 3));
+        assert!(parse_color("1, 2").is_err());
+        assert!(parse_color("1, 2, 300").is_err());
+    }
+}
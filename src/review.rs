@@ -0,0 +1,175 @@
+use crate::game_model::{GameModel, Move, State};
+use crate::solver::{analyze, Analysis};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    // the cell was logically safe
+    Safe,
+    // there were no safe cells, guessing was unavoidable
+    ForcedGuess,
+    // a risky cell was opened while a safe one was known
+    Blunder,
+}
+
+// Post-game walk through the move log, every position is analyzed as seen by the player
+pub struct Review {
+    game: GameModel,
+    step: usize,
+    position: GameModel,
+    analysis: Analysis,
+}
+
+impl Review {
+    // Starts at the position before the fatal click for lost games, at the end otherwise
+    pub fn new(game: &GameModel) -> Self {
+        let game = game.position_after(game.moves().len());
+        let count = game.moves().len();
+        let step = if game.state() == State::Lose {
+            count.saturating_sub(1)
+        } else {
+            count
+        };
+        let position = game.position_after(step);
+        let analysis = analyze(&position.player_view());
+        Self {
+            game,
+            step,
+            position,
+            analysis,
+        }
+    }
+
+    pub fn moves_count(&self) -> usize {
+        self.game.moves().len()
+    }
+
+    pub fn go_to(&mut self, step: usize) {
+        let step = step.min(self.moves_count());
+        if step == self.step {
+            return;
+        }
+        self.step = step;
+        self.position = self.game.position_after(step);
+        self.analysis = analyze(&self.position.player_view());
+    }
+
+    pub fn go_back(&mut self) {
+        self.go_to(self.step.saturating_sub(1));
+    }
+
+    pub fn go_forward(&mut self) {
+        self.go_to(self.step + 1);
+    }
+
+    // Board before the next move
+    pub fn position(&self) -> &GameModel {
+        &self.position
+    }
+
+    pub fn analysis(&self) -> &Analysis {
+        &self.analysis
+    }
+
+    pub fn next_move(&self) -> Option<Move> {
+        self.game.moves().get(self.step).copied()
+    }
+
+    // Mine probability of the opened cell and how reasonable the click was
+    pub fn judge_next_move(&self) -> Option<(f64, Verdict)> {
+        let Some(Move::Open(row, col)) = self.next_move() else {
+            return None;
+        };
+        let probability = self.analysis.probability(row, col)?;
+        let verdict = if probability == 0.0 {
+            Verdict::Safe
+        } else if self.analysis.safe_cells().is_empty() {
+            Verdict::ForcedGuess
+        } else {
+            Verdict::Blunder
+        };
+        Some((probability, verdict))
+    }
+
+    // One line description of the current step
+    pub fn summary(&self) -> String {
+        let header = format!("Move {}/{}", self.step + 1, self.moves_count());
+        let Some(action) = self.next_move() else {
+            return format!("End of game, {} moves", self.moves_count());
+        };
+        let judgement = match self.judge_next_move() {
+            Some((probability, verdict)) => {
+                let verdict = match verdict {
+                    Verdict::Safe => "safe",
+                    Verdict::ForcedGuess => "forced guess",
+                    Verdict::Blunder => "blunder",
+                };
+                format!(" - mine {:.0}% - {verdict}", probability * 100.0)
+            }
+            None => String::new(),
+        };
+        match action {
+            Move::Open(row, col) => format!("{header}: open {row}:{col}{judgement}"),
+            Move::Flag(row, col) => format!("{header}: flag {row}:{col}"),
+            Move::Chord(row, col) => format!("{header}: chord {row}:{col}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_model::replay;
+
+    #[test]
+    fn review_steps_through_won_game() {
+        // after the first move the middle right cell is known to be safe
+        let game = replay::parse(
+            "..*
+            ...
+            ..*
+            open 0 0
+            open 1 2",
+        )
+        .unwrap();
+        assert_eq!(game.state(), State::Win);
+        let mut review = Review::new(&game);
+        assert_eq!(review.moves_count(), 2);
+        assert_eq!(review.step, 2);
+        assert!(review.judge_next_move().is_none());
+        assert_eq!(review.summary(), "End of game, 2 moves");
+
+        review.go_back();
+        assert_eq!(review.next_move(), Some(Move::Open(1, 2)));
+        assert_eq!(review.judge_next_move(), Some((0.0, Verdict::Safe)));
+
+        review.go_back();
+        assert_eq!(review.step, 0);
+        assert!(!review.position().get_cell(0, 0).is_visible());
+        let (probability, verdict) = review.judge_next_move().unwrap();
+        assert!((probability - 2.0 / 9.0).abs() < 1e-9);
+        assert_eq!(verdict, Verdict::ForcedGuess);
+
+        review.go_to(10);
+        assert_eq!(review.step, 2);
+    }
+
+    #[test]
+    fn review_starts_before_fatal_click() {
+        let game = replay::parse(
+            "*..
+            ...
+            ..*
+            open 0 2
+            open 0 0",
+        )
+        .unwrap();
+        assert_eq!(game.state(), State::Lose);
+        let review = Review::new(&game);
+        assert_eq!(review.step, 1);
+        let (probability, verdict) = review.judge_next_move().unwrap();
+        assert!(probability > 0.0);
+        assert_eq!(verdict, Verdict::Blunder);
+        assert!(review.summary().starts_with("Move 2/2: open 0:0 - mine "));
+        assert!(review.summary().ends_with("% - blunder"));
+    }
+}
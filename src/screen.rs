@@ -0,0 +1,354 @@
+// Parts shared by the game screens: the frame loop, mouse presses and drags, cell sprites and
+// the drawing helpers. A screen keeps its model and camera and decides what the input does
+use std::collections::HashSet;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+use crate::bindings::KeyAction;
+use crate::game_model::Cell;
+use crate::minesweeper::Size;
+use crate::render::{MouseSnapshot, Renderer, Sprite};
+use crate::resources::ColorManager;
+
+const TARGET_FPS: u64 = 24;
+// Mouse movement distance after which pressed left button pans the board instead of clicking
+const DRAG_THRESHOLD: i32 = 5;
+
+pub(crate) trait Screen {
+    fn handle_events(&mut self);
+    fn is_running(&self) -> bool;
+    fn draw(&mut self) -> Result<(), String>;
+    // Time passed since the previous frame
+    fn advance(&mut self, _elapsed: Duration) {}
+}
+
+pub(crate) fn run(screen: &mut impl Screen) -> Result<(), String> {
+    let target_frame_duration = Duration::from_millis(1000u64 / TARGET_FPS);
+    let mut prev_frame_start_time = Instant::now();
+    loop {
+        let frame_start_time = Instant::now();
+        screen.advance(frame_start_time - prev_frame_start_time);
+        prev_frame_start_time = frame_start_time;
+        screen.handle_events();
+        if !screen.is_running() {
+            break;
+        }
+        screen.draw()?;
+        let elapsed_time = frame_start_time.elapsed();
+        let sleep_time = target_frame_duration.saturating_sub(elapsed_time);
+        if sleep_time.is_zero() {
+            println!("Frame elapsed time {elapsed_time:?}");
+            continue;
+        }
+        ::std::thread::sleep(sleep_time);
+    }
+    Ok(())
+}
+
+#[derive(Copy, Clone)]
+struct Drag {
+    start: Point,
+    last: Point,
+    is_active: bool,
+}
+
+// Mouse buttons held since the first one went down and the cell pressed at that moment
+#[derive(Clone)]
+pub(crate) struct Press<C> {
+    pub cell: Option<C>,
+    pub buttons: HashSet<MouseButton>,
+}
+
+impl<C> Press<C> {
+    // middle button or both left and right open neighbours of a number
+    pub fn is_chord(&self) -> bool {
+        self.buttons.contains(&MouseButton::Middle)
+            || self.buttons.contains(&MouseButton::Left)
+                && self.buttons.contains(&MouseButton::Right)
+    }
+
+    // The buttons do what the keys of the action do with the cursor
+    pub fn action(&self) -> Option<KeyAction> {
+        if self.is_chord() {
+            Some(KeyAction::Chord)
+        } else if self.buttons.contains(&MouseButton::Left) {
+            Some(KeyAction::Open)
+        } else if self.buttons.contains(&MouseButton::Right) {
+            Some(KeyAction::Flag)
+        } else {
+            None
+        }
+    }
+}
+
+// Mouse over the board: held buttons and the left button drag panning the board
+pub(crate) struct Pointer<C> {
+    position: Point,
+    press: Option<Press<C>>,
+    drag: Option<Drag>,
+}
+
+impl<C: Copy + PartialEq> Pointer<C> {
+    pub fn new() -> Self {
+        Self {
+            position: Point::new(0, 0),
+            press: None,
+            drag: None,
+        }
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.press.is_some()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some_and(|drag| drag.is_active)
+    }
+
+    // Forgets the held buttons, for example when the board under them is replaced
+    pub fn cancel(&mut self) {
+        self.press = None;
+    }
+
+    // Follows the mouse, `cell` is the cell under it. A left button drag starting while
+    // `can_drag` passes its movement to `pan`. Returns the press ended by releasing the
+    // buttons without a drag, its cell is cleared when released outside the pressed cell
+    pub fn update(
+        &mut self,
+        state: MouseSnapshot,
+        cell: Option<C>,
+        can_drag: bool,
+        pan: impl FnMut(Point),
+    ) -> Option<Press<C>> {
+        self.position = Point::new(state.x, state.y);
+        let is_left = state.buttons.contains(&MouseButton::Left);
+        let is_dragged = self.track_drag(is_left, can_drag, pan);
+        if !state.buttons.is_empty() {
+            let press = self.press.get_or_insert_with(|| Press {
+                cell,
+                buttons: HashSet::new(),
+            });
+            press.buttons.extend(state.buttons);
+            return None;
+        }
+        let mut press = self.press.take().filter(|_| !is_dragged)?;
+        if press.cell != cell {
+            press.cell = None;
+        }
+        Some(press)
+    }
+
+    // Returns true if the drag happened
+    fn track_drag(&mut self, is_pressed: bool, can_drag: bool, mut pan: impl FnMut(Point)) -> bool {
+        let position = self.position;
+        let Some(mut drag) = self.drag else {
+            if is_pressed && can_drag {
+                self.drag = Some(Drag {
+                    start: position,
+                    last: position,
+                    is_active: false,
+                });
+            }
+            return false;
+        };
+        let offset = position - drag.start;
+        drag.is_active |= offset.x().abs().max(offset.y().abs()) > DRAG_THRESHOLD;
+        if drag.is_active {
+            pan(position - drag.last);
+        }
+        drag.last = position;
+        self.drag = is_pressed.then_some(drag);
+        drag.is_active
+    }
+
+    // Cells drawn depressed while the buttons are held over the pressed cell: the cell itself
+    // for opening, its neighbours too for chording. The screen keeps the covered ones
+    pub fn depressed_cells<N: IntoIterator<Item = C>>(
+        &self,
+        hovered: Option<C>,
+        neighbours: impl FnOnce(C) -> N,
+    ) -> Vec<C> {
+        let Some(press) = &self.press else {
+            return Vec::new();
+        };
+        let Some(cell) = press.cell else {
+            return Vec::new();
+        };
+        if self.is_dragging() || hovered != Some(cell) {
+            return Vec::new();
+        }
+        match press.action() {
+            Some(KeyAction::Chord) => [cell].into_iter().chain(neighbours(cell)).collect(),
+            Some(KeyAction::Open) => vec![cell],
+            _ => Vec::new(),
+        }
+    }
+}
+
+// After the loss mines are shown except correctly flagged ones and wrong flags are marked
+pub(crate) fn cell_sprite(cell: Cell, is_lose: bool, is_exploded: bool) -> Sprite {
+    if is_exploded {
+        Sprite::BombExploded
+    } else if cell.is_flagged() {
+        if is_lose && cell.is_safe() {
+            Sprite::WrongFlag
+        } else {
+            Sprite::Flag
+        }
+    } else if is_lose && !cell.is_safe() {
+        Sprite::Bomb
+    } else if cell.is_visible() {
+        match cell.mines_count() {
+            0 => Sprite::Empty,
+            count => Sprite::Number(count),
+        }
+    } else {
+        Sprite::Unknown
+    }
+}
+
+pub(crate) fn draw_outline(
+    renderer: &mut impl Renderer,
+    color: Color,
+    rect: Rect,
+) -> Result<(), String> {
+    let (x, y, w, h) = (rect.x(), rect.y(), rect.width(), rect.height());
+    renderer.fill_rect(color, Rect::new(x, y, w, 1))?;
+    renderer.fill_rect(color, Rect::new(x, rect.bottom() - 1, w, 1))?;
+    renderer.fill_rect(color, Rect::new(x, y, 1, h))?;
+    renderer.fill_rect(color, Rect::new(rect.right() - 1, y, 1, h))
+}
+
+// Outline inside the cell thick enough to be seen at any zoom
+pub(crate) fn draw_frame(
+    renderer: &mut impl Renderer,
+    color: Color,
+    cell: Rect,
+) -> Result<(), String> {
+    let thickness = (cell.height() / 16).max(2);
+    for inset in 0..thickness {
+        let rect = Rect::new(
+            cell.x() + inset as i32,
+            cell.y() + inset as i32,
+            cell.width().saturating_sub(2 * inset).max(1),
+            cell.height().saturating_sub(2 * inset).max(1),
+        );
+        draw_outline(renderer, color, rect)?;
+    }
+    Ok(())
+}
+
+// Line of text at the top of the window
+pub(crate) fn show_status(
+    renderer: &mut impl Renderer,
+    color_manager: &ColorManager,
+    window_size: &Size,
+    text: &str,
+) -> Result<(), String> {
+    let w = window_size.width;
+    let height = (window_size.height / 14).clamp(16, 40);
+    renderer.fill_rect(color_manager.fade(), Rect::new(0, 0, w, height))?;
+    // glyphs are about half as wide as high
+    let width = (height / 2 * text.chars().count() as u32).min(w);
+    let x = (w - width) / 2;
+    let frame = Rect::new(x as i32, 0, width.max(1), height);
+    renderer.draw_text(text, color_manager.title_message(), frame)
+}
+
+// Faded window with the text in the middle
+pub(crate) fn show_message(
+    renderer: &mut impl Renderer,
+    color_manager: &ColorManager,
+    window_size: &Size,
+    text: &str,
+) -> Result<(), String> {
+    let (w, h) = (window_size.width, window_size.height);
+    renderer.fill_rect(color_manager.fade(), Rect::new(0, 0, w, h))?;
+
+    // banner keeps 650x150 proportions taking at most 80% of the window width
+    let width = (w * 4 / 5).min(650);
+    let size = Size {
+        height: width * 150 / 650,
+        width,
+    };
+    let x = w.saturating_sub(size.width) / 2;
+    let y = h.saturating_sub(size.height) / 2;
+    let frame = Rect::new(x as i32, y as i32, size.width, size.height);
+    renderer.draw_text(text, color_manager.title_message(), frame)
+}
+
+// Milliseconds since the epoch, unique enough for file names of a single player
+pub(crate) fn timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mouse(x: i32, buttons: &[MouseButton]) -> MouseSnapshot {
+        MouseSnapshot {
+            x,
+            y: 0,
+            buttons: buttons.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn pointer_press_and_release() {
+        let mut pointer = Pointer::new();
+        let both = [MouseButton::Left, MouseButton::Right];
+        assert!(pointer
+            .update(mouse(0, &both[..1]), Some(1), false, |_| {})
+            .is_none());
+        assert!(pointer
+            .update(mouse(0, &both), Some(1), false, |_| {})
+            .is_none());
+        assert_eq!(
+            pointer.depressed_cells(Some(1), |cell| [cell + 1]),
+            vec![1, 2]
+        );
+        assert!(pointer
+            .depressed_cells(Some(2), |cell| [cell + 1])
+            .is_empty());
+        let press = pointer.update(mouse(0, &[]), Some(1), false, |_| {});
+        let press = press.unwrap();
+        assert_eq!(
+            (press.cell, press.action()),
+            (Some(1), Some(KeyAction::Chord))
+        );
+
+        // releasing outside the pressed cell cancels the action
+        pointer.update(mouse(0, &[MouseButton::Right]), Some(1), false, |_| {});
+        let press = pointer.update(mouse(0, &[]), Some(2), false, |_| {});
+        assert_eq!(press.unwrap().cell, None);
+    }
+
+    #[test]
+    fn pointer_drag_pans_instead_of_click() {
+        let mut pointer = Pointer::new();
+        let mut panned = Vec::new();
+        let left = [MouseButton::Left];
+        for x in [0, 3, 20] {
+            pointer.update(mouse(x, &left), Some(0), true, |delta| {
+                panned.push(delta.x())
+            });
+        }
+        assert!(pointer.is_dragging());
+        assert!(pointer.depressed_cells(Some(0), |_| []).is_empty());
+        let press = pointer.update(mouse(20, &[]), Some(0), true, |delta| {
+            panned.push(delta.x())
+        });
+        assert!(press.is_none());
+        assert_eq!(panned, vec![17, 0]);
+    }
+}
@@ -0,0 +1,588 @@
+use std::collections::HashMap;
+
+use crate::game_model::{CellView, GameModel, PlayerView, State};
+
+// Upper bound of search nodes for a single frontier component,
+// components exceeding it get approximate probabilities
+const SEARCH_LIMIT: usize = 2_000_000;
+
+type Coordinate = (usize, usize);
+
+// Mine probabilities for every covered cell of the board
+pub struct Analysis {
+    probabilities: Vec<Vec<Option<f64>>>,
+}
+
+impl Analysis {
+    // None for opened cells
+    pub fn probability(&self, row: usize, col: usize) -> Option<f64> {
+        self.probabilities[row][col]
+    }
+
+    pub fn safe_cells(&self) -> Vec<Coordinate> {
+        self.cells_matching(|p| p == 0.0)
+    }
+
+    // Covered cells having the lowest mine probability
+    pub fn safest_cells(&self) -> Vec<Coordinate> {
+        let min = self
+            .probabilities
+            .iter()
+            .flatten()
+            .flatten()
+            .fold(f64::INFINITY, |acc, p| acc.min(*p));
+        self.cells_matching(|p| p == min)
+    }
+
+    fn cells_matching(&self, predicate: impl Fn(f64) -> bool) -> Vec<Coordinate> {
+        let mut cells = Vec::new();
+        for (row, line) in self.probabilities.iter().enumerate() {
+            for (col, probability) in line.iter().enumerate() {
+                if matches!(probability, Some(p) if predicate(*p)) {
+                    cells.push((row, col));
+                }
+            }
+        }
+        cells
+    }
+}
+
+// Computes mine probabilities using only the information visible to a player.
+// Flags are ignored as they may be wrong. When cells may hold several mines it's the
+// probability of at least one
+pub fn analyze(view: &PlayerView) -> Analysis {
+    let (rows, cols) = view.board_size();
+    let max_cell_mines = view.max_cell_mines();
+    let mut probabilities = vec![vec![None; cols]; rows];
+
+    let (constraints, frontier) = collect_constraints(view);
+
+    let mut others = Vec::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            if is_covered(view, row, col) && !frontier.contains_key(&(row, col)) {
+                others.push((row, col));
+            }
+        }
+    }
+
+    let mut mines_left = view.mines_count();
+    let mut solutions = Vec::new();
+    for component in split_components(&constraints, &frontier) {
+        match component.enumerate(mines_left, max_cell_mines) {
+            Some(solution) => solutions.push(solution),
+            None => {
+                let expected = component.estimate(&mut probabilities);
+                mines_left = mines_left.saturating_sub(expected.round() as usize);
+            }
+        }
+    }
+
+    // every cell outside of the frontier is a group of slots a mine may take
+    let binomials = Binomials::new(others.len() * max_cell_mines, mines_left);
+    let total_counts = convolve(solutions.iter().map(|s| s.counts.as_slice()));
+    let total = weigh(&total_counts, 0, &binomials);
+    if total == 0.0 {
+        // inconsistent view, nothing better than uniform distribution
+        let covered = others.len() + frontier.len();
+        let density = view.mines_count() as f64 / covered.max(1) as f64;
+        for (row, col) in others.iter().chain(frontier.keys()) {
+            probabilities[*row][*col] = Some(density.min(1.0));
+        }
+        return Analysis { probabilities };
+    }
+
+    for (index, solution) in solutions.iter().enumerate() {
+        let rest = convolve(
+            solutions
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .map(|(_, s)| s.counts.as_slice()),
+        );
+        let weights = (0..solution.counts.len())
+            .map(|k| weigh(&rest, k, &binomials))
+            .collect::<Vec<_>>();
+        let denominator = dot(&solution.counts, &weights);
+        for (cell, mine_counts) in solution.cells.iter().zip(&solution.mine_counts) {
+            let numerator = dot(mine_counts, &weights);
+            probabilities[cell.0][cell.1] = Some(numerator / denominator);
+        }
+    }
+
+    if !others.is_empty() {
+        let mined = total_counts
+            .iter()
+            .enumerate()
+            .filter_map(|(k, count)| {
+                let rest = mines_left.checked_sub(k)?;
+                let empty = binomials.share_avoiding(max_cell_mines, rest);
+                Some(count * binomials.weight(rest) * (1.0 - empty))
+            })
+            .sum::<f64>();
+        let density = mined / total;
+        for (row, col) in others {
+            probabilities[row][col] = Some(density);
+        }
+    }
+    Analysis { probabilities }
+}
+
+// Covered cells proven safe by the numbers alone, without the count of mines left
+pub fn local_safe_cells(view: &PlayerView) -> Vec<Coordinate> {
+    let (constraints, frontier) = collect_constraints(view);
+    let mut cells = Vec::new();
+    for component in split_components(&constraints, &frontier) {
+        // components too large to enumerate prove nothing
+        let Some(solution) = component.enumerate(usize::MAX, view.max_cell_mines()) else {
+            continue;
+        };
+        if solution.counts.iter().all(|count| *count == 0.0) {
+            continue;
+        }
+        for (cell, mine_counts) in solution.cells.iter().zip(&solution.mine_counts) {
+            if mine_counts.iter().all(|count| *count == 0.0) {
+                cells.push(*cell);
+            }
+        }
+    }
+    cells.sort();
+    cells
+}
+
+// Plays the game opening only cells proven safe by the numbers, true if that wins it: the
+// board never needs a guess nor the count of mines left
+pub fn is_solvable_without_guessing(model: &mut GameModel) -> bool {
+    while model.state() == State::InProgress {
+        let safe = local_safe_cells(&model.player_view());
+        if safe.is_empty() {
+            return false;
+        }
+        for (row, col) in safe {
+            model.open_cell(row, col);
+        }
+    }
+    model.state() == State::Win
+}
+
+fn is_covered(view: &PlayerView, row: usize, col: usize) -> bool {
+    matches!(view.cell(row, col), CellView::Hidden | CellView::Flagged(_))
+}
+
+// Constraints of the opened numbers and, for every covered neighbour, the ones it takes part in
+fn collect_constraints(view: &PlayerView) -> (Vec<Constraint>, HashMap<Coordinate, Vec<usize>>) {
+    let (rows, cols) = view.board_size();
+    let mut constraints = Vec::new();
+    let mut frontier: HashMap<Coordinate, Vec<usize>> = HashMap::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            let CellView::Revealed(mines) = view.cell(row, col) else {
+                continue;
+            };
+            let cells = view
+                .neighbours(row, col)
+                .filter(|(r, c)| is_covered(view, *r, *c))
+                .collect::<Vec<_>>();
+            if cells.is_empty() {
+                continue;
+            }
+            for cell in &cells {
+                frontier.entry(*cell).or_default().push(constraints.len());
+            }
+            constraints.push(Constraint { cells, mines });
+        }
+    }
+    (constraints, frontier)
+}
+
+struct Constraint {
+    cells: Vec<Coordinate>,
+    mines: usize,
+}
+
+// Set of covered cells linked together by shared constraints
+struct Component<'a> {
+    cells: Vec<Coordinate>,
+    constraints: Vec<&'a Constraint>,
+}
+
+// Number of valid mine arrangements of a component grouped by mines used
+struct Solution {
+    cells: Vec<Coordinate>,
+    counts: Vec<f64>,
+    mine_counts: Vec<Vec<f64>>,
+}
+
+fn split_components<'a>(
+    constraints: &'a [Constraint],
+    frontier: &HashMap<Coordinate, Vec<usize>>,
+) -> Vec<Component<'a>> {
+    let mut visited = vec![false; constraints.len()];
+    let mut components = Vec::new();
+    for start in 0..constraints.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut queue = vec![start];
+        let mut cells = Vec::new();
+        let mut members = Vec::new();
+        let mut position = 0;
+        // breadth first order keeps related cells close to each other for early pruning
+        while position < queue.len() {
+            let current = queue[position];
+            position += 1;
+            members.push(&constraints[current]);
+            for cell in &constraints[current].cells {
+                if !cells.contains(cell) {
+                    cells.push(*cell);
+                }
+                for next in &frontier[cell] {
+                    if !visited[*next] {
+                        visited[*next] = true;
+                        queue.push(*next);
+                    }
+                }
+            }
+        }
+        components.push(Component {
+            cells,
+            constraints: members,
+        });
+    }
+    components
+}
+
+impl<'a> Component<'a> {
+    fn enumerate(&self, mines_limit: usize, max_cell_mines: usize) -> Option<Solution> {
+        let most = self.cells.len() * max_cell_mines;
+        let index = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (*cell, i))
+            .collect::<HashMap<_, _>>();
+        let mut search = Search {
+            links: vec![vec![]; self.cells.len()],
+            targets: self.constraints.iter().map(|c| c.mines).collect(),
+            assigned: vec![0; self.constraints.len()],
+            unassigned: self.constraints.iter().map(|c| c.cells.len()).collect(),
+            values: vec![0; self.cells.len()],
+            ways: (0..=max_cell_mines)
+                .map(|mines| binomial(max_cell_mines, mines))
+                .collect(),
+            mines: 0,
+            mines_limit,
+            nodes: 0,
+            counts: vec![0.0; most + 1],
+            mine_counts: vec![vec![0.0; most + 1]; self.cells.len()],
+        };
+        for (c, constraint) in self.constraints.iter().enumerate() {
+            for cell in &constraint.cells {
+                search.links[index[cell]].push(c);
+            }
+        }
+        if !search.run(0) {
+            return None;
+        }
+        let scale = search.counts.iter().fold(0.0f64, |acc, v| acc.max(*v));
+        if scale > 0.0 {
+            search.counts.iter_mut().for_each(|v| *v /= scale);
+            search
+                .mine_counts
+                .iter_mut()
+                .flatten()
+                .for_each(|v| *v /= scale);
+        }
+        Some(Solution {
+            cells: self.cells.clone(),
+            counts: search.counts,
+            mine_counts: search.mine_counts,
+        })
+    }
+
+    // Rough local estimation, returns expected number of mines in the component
+    fn estimate(&self, probabilities: &mut [Vec<Option<f64>>]) -> f64 {
+        let mut sums: HashMap<Coordinate, (f64, usize)> = HashMap::new();
+        for constraint in &self.constraints {
+            let local = constraint.mines as f64 / constraint.cells.len() as f64;
+            for cell in &constraint.cells {
+                let entry = sums.entry(*cell).or_default();
+                entry.0 += local;
+                entry.1 += 1;
+            }
+        }
+        let mut expected = 0.0;
+        for ((row, col), (sum, count)) in sums {
+            let mines = sum / count as f64;
+            probabilities[row][col] = Some(mines.min(1.0));
+            expected += mines;
+        }
+        expected
+    }
+}
+
+struct Search {
+    links: Vec<Vec<usize>>,
+    targets: Vec<usize>,
+    assigned: Vec<usize>,
+    unassigned: Vec<usize>,
+    // mines in every cell
+    values: Vec<usize>,
+    // arrangements of the given number of mines among the slots of a cell
+    ways: Vec<f64>,
+    mines: usize,
+    mines_limit: usize,
+    nodes: usize,
+    counts: Vec<f64>,
+    mine_counts: Vec<Vec<f64>>,
+}
+
+impl Search {
+    // Returns false if search limit is exceeded
+    fn run(&mut self, var: usize) -> bool {
+        self.nodes += 1;
+        if self.nodes > SEARCH_LIMIT {
+            return false;
+        }
+        if var == self.values.len() {
+            let weight = self.values.iter().map(|v| self.ways[*v]).product::<f64>();
+            self.counts[self.mines] += weight;
+            for (v, mines) in self.values.iter().enumerate() {
+                if *mines > 0 {
+                    self.mine_counts[v][self.mines] += weight;
+                }
+            }
+            return true;
+        }
+        for mines in 0..self.ways.len() {
+            if self.mines + mines > self.mines_limit {
+                break;
+            }
+            if self.assign(var, mines) && !self.run(var + 1) {
+                return false;
+            }
+            self.unassign(var, mines);
+        }
+        true
+    }
+
+    fn assign(&mut self, var: usize, mines: usize) -> bool {
+        let most = self.ways.len() - 1;
+        self.values[var] = mines;
+        self.mines += mines;
+        let mut is_valid = true;
+        for c in &self.links[var] {
+            self.unassigned[*c] -= 1;
+            self.assigned[*c] += mines;
+            let (assigned, target) = (self.assigned[*c], self.targets[*c]);
+            is_valid &= assigned <= target && assigned + self.unassigned[*c] * most >= target;
+        }
+        is_valid
+    }
+
+    fn unassign(&mut self, var: usize, mines: usize) {
+        self.values[var] = 0;
+        self.mines -= mines;
+        for c in &self.links[var] {
+            self.unassigned[*c] += 1;
+            self.assigned[*c] -= mines;
+        }
+    }
+}
+
+// Binomial coefficients C(n, k) for fixed n, scaled to avoid overflow
+struct Binomials {
+    n: usize,
+    mines: usize,
+    shift: f64,
+    ln_factorials: Vec<f64>,
+}
+
+impl Binomials {
+    fn new(n: usize, mines: usize) -> Self {
+        let mut ln_factorials = vec![0.0; n + 1];
+        for i in 1..=n {
+            ln_factorials[i] = ln_factorials[i - 1] + (i as f64).ln();
+        }
+        let mut binomials = Self {
+            n,
+            mines,
+            shift: 0.0,
+            ln_factorials,
+        };
+        binomials.shift = binomials.ln_binomial(n / 2).unwrap_or(0.0);
+        binomials
+    }
+
+    fn ln_binomial(&self, k: usize) -> Option<f64> {
+        self.ln_binomial_of(self.n, k)
+    }
+
+    fn ln_binomial_of(&self, n: usize, k: usize) -> Option<f64> {
+        if k > n {
+            return None;
+        }
+        let f = &self.ln_factorials;
+        Some(f[n] - f[k] - f[n - k])
+    }
+
+    // Share of the ways to place `k` mines that leave the given slots free
+    fn share_avoiding(&self, slots: usize, k: usize) -> f64 {
+        let Some(rest) = self.n.checked_sub(slots) else {
+            return 0.0;
+        };
+        match (self.ln_binomial_of(rest, k), self.ln_binomial(k)) {
+            (Some(avoiding), Some(all)) => (avoiding - all).exp(),
+            _ => 0.0,
+        }
+    }
+
+    // Relative number of ways to place `k` mines outside of the frontier
+    fn weight(&self, k: usize) -> f64 {
+        self.ln_binomial(k)
+            .map_or(0.0, |value| (value - self.shift).exp())
+    }
+}
+
+// Weight of frontier arrangements using `used` mines in one component and
+// the rest distributed according to `counts` plus cells outside of the frontier
+fn weigh(counts: &[f64], used: usize, binomials: &Binomials) -> f64 {
+    counts
+        .iter()
+        .enumerate()
+        .filter_map(|(k, count)| {
+            let rest = binomials.mines.checked_sub(k + used)?;
+            Some(count * binomials.weight(rest))
+        })
+        .sum()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+fn convolve<'a>(parts: impl Iterator<Item = &'a [f64]>) -> Vec<f64> {
+    parts.fold(vec![1.0], |acc, part| {
+        let mut result = vec![0.0; acc.len() + part.len() - 1];
+        for (i, a) in acc.iter().enumerate() {
+            for (j, b) in part.iter().enumerate() {
+                result[i + j] += a * b;
+            }
+        }
+        result
+    })
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_model::{layout, GameModel, Grid};
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("probability expected");
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn solver_without_guessing() {
+        // both mines are certain after the corner opens the left cells
+        let mut game = layout::parse("o.*\n..*").unwrap();
+        assert!(is_solvable_without_guessing(&mut game));
+        assert_eq!(game.state(), State::Win);
+        // one mine in either of the right cells
+        let mut game = layout::parse("o.*\n...").unwrap();
+        assert!(!is_solvable_without_guessing(&mut game));
+        assert_eq!(game.state(), State::InProgress);
+        // the right cell is safe only for the count of mines, which the player doesn't see
+        let mut game = layout::parse("o*.").unwrap();
+        assert_eq!(analyze(&game.player_view()).safe_cells(), vec![(0, 2)]);
+        assert!(local_safe_cells(&game.player_view()).is_empty());
+        assert!(!is_solvable_without_guessing(&mut game));
+    }
+
+    #[test]
+    fn solver_initial_board_uniform() {
+        let mut game = GameModel::with_seed(1);
+        game.start(9, 9, 10).unwrap();
+        let analysis = analyze(&game.player_view());
+        assert_close(analysis.probabilities[4][4], 10.0 / 81.0);
+        assert!(analysis.safe_cells().is_empty());
+    }
+
+    #[test]
+    fn solver_never_marks_mine_as_safe() {
+        for seed in 0..20 {
+            let mut game = GameModel::with_seed(seed);
+            game.start(9, 9, 10).unwrap();
+            let (row, col) = (0..81)
+                .map(|i| (i / 9, i % 9))
+                .find(|(r, c)| {
+                    let cell = game.get_cell(*r, *c);
+                    cell.is_safe() && cell.mines_count() == 0
+                })
+                .unwrap();
+            game.open_cell(row, col);
+            let analysis = analyze(&game.player_view());
+            for (r, c) in analysis.safe_cells() {
+                assert!(game.get_cell(r, c).is_safe());
+            }
+            for (r, c) in analysis.cells_matching(|p| p == 1.0) {
+                assert!(!game.get_cell(r, c).is_safe());
+            }
+        }
+    }
+
+    #[test]
+    fn solver_follows_neighbourhood() {
+        // the opened corner sees only the two cells a knight's move away
+        let game = layout::parse("grid knight\no..\n...\n.*.").unwrap();
+        assert_eq!(game.get_cell(0, 0).mines_count(), 1);
+        let analysis = analyze(&game.player_view());
+        assert_close(analysis.probability(1, 2), 0.5);
+        assert_close(analysis.probability(2, 1), 0.5);
+        assert_close(analysis.probability(1, 1), 0.0);
+
+        for grid in Grid::ALL {
+            for seed in 0..5 {
+                let mut game = GameModel::with_seed(seed);
+                game.set_grid(grid);
+                game.start(8, 8, 12).unwrap();
+                let safe = (0..64)
+                    .map(|i| (i / 8, i % 8))
+                    .find(|&(r, c)| game.get_cell(r, c).is_safe())
+                    .unwrap();
+                game.open_cell(safe.0, safe.1);
+                let analysis = analyze(&game.player_view());
+                for (r, c) in analysis.safe_cells() {
+                    assert!(game.get_cell(r, c).is_safe(), "{grid:?}");
+                }
+                for (r, c) in analysis.cells_matching(|p| p == 1.0) {
+                    assert!(!game.get_cell(r, c).is_safe(), "{grid:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solver_multi_mine_cells() {
+        // nothing opened: 4 mines among the 18 slots of 9 cells
+        let mut game = GameModel::with_seed(3);
+        game.set_max_cell_mines(2);
+        game.start(3, 3, 4).unwrap();
+        let analysis = analyze(&game.player_view());
+        assert_close(analysis.probabilities[1][1], 1.0 - 1820.0 / 3060.0);
+
+        // the number needs two mines: both in one neighbour once each way, or one in each
+        // twice each way
+        let game = layout::parse("cell_mines 2\n2o..").unwrap();
+        let analysis = analyze(&game.player_view());
+        assert_close(analysis.probabilities[0][0], 5.0 / 6.0);
+        assert_close(analysis.probabilities[0][2], 5.0 / 6.0);
+        assert_close(analysis.probabilities[0][3], 0.0);
+    }
+}